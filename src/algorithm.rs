@@ -0,0 +1,145 @@
+use diffhelpers::{EditFlags, make_diffs};
+use longest_common_subseq::build_lcs_table;
+use myers::{myers_diffs, linear_myers_diffs};
+use patience::patience_diffs;
+use histogram::histogram_diffs;
+use std::hash::Hash;
+
+/// The largest LCS table (in cells) diff() is willing to build before
+/// switching to an algorithm that doesn't need one
+const LCS_TABLE_LIMIT: usize = 1 << 22;
+
+/// The Algorithm enum. Selects how the edit script is calculated.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Algorithm {
+    /// Backtracks through a full longest common subsequence table.
+    /// Needs (N+1)x(M+1) memory, so it is only suited to small inputs
+    Lcs,
+    /// Myers' greedy O((N+M)D) algorithm. Fast when the inputs are similar,
+    /// but keeps O(D^2) state around for backtracking
+    Myers,
+    /// Myers' divide-and-conquer refinement, which bisects the inputs around
+    /// the middle snake. Same running time, O(N+M) memory
+    LinearMyers,
+    /// Patience diff. Anchors on items that are unique in both inputs,
+    /// which keeps hunks of source code from aligning on braces and blank lines
+    Patience,
+    /// Histogram diff, as in git. Splits around the rarest common item, so it
+    /// still finds good anchors when few items are unique
+    Histogram,
+}
+
+impl Algorithm {
+    /// The algorithm diff() uses for inputs of the given lengths
+    ///
+    /// # Example
+    /// ```
+    /// use diff::Algorithm;
+    ///
+    /// assert_eq!(Algorithm::for_lengths(3, 3), Algorithm::Lcs);
+    /// assert_eq!(Algorithm::for_lengths(50_000, 50_000), Algorithm::LinearMyers);
+    /// ```
+    pub fn for_lengths(from: usize, to: usize) -> Algorithm {
+        match (from + 1).checked_mul(to + 1) {
+            Some(cells) if cells <= LCS_TABLE_LIMIT => Algorithm::Lcs,
+            _ => Algorithm::LinearMyers,
+        }
+    }
+}
+
+/// A way of calculating the edits necessary to make "from" match "to".
+///
+/// Implement this to plug a custom aligner into diff_using; the resulting
+/// edits are merged into DiffItems the same way as for the built in algorithms.
+///
+/// # Example
+/// ```
+/// use diff::{diff_using, DiffAlgorithm, EditFlags};
+///
+/// /// Never matches anything: deletes all of "from" and adds all of "to"
+/// struct Replace;
+///
+/// impl<T> DiffAlgorithm<T> for Replace {
+///     fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+///         let mut diffs = vec![EditFlags::Delete; from.len()];
+///         diffs.extend(vec![EditFlags::Add; to.len()]);
+///         diffs
+///     }
+/// }
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+/// let changes = diff_using(&from, &to, &Replace);
+/// assert_eq!(changes.len(), 1);
+/// ```
+pub trait DiffAlgorithm<T> {
+    /// Returns one EditFlags per step through the two sequences, in order:
+    /// Same consumes an item from both (which must be equal), Delete one from
+    /// "from" and Add one from "to".
+    fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags>;
+}
+
+/// Backtracks through a full longest common subsequence table (Algorithm::Lcs)
+#[derive(Debug, Clone, Copy)]
+pub struct Lcs;
+
+impl<T: PartialEq> DiffAlgorithm<T> for Lcs {
+    fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+        let table = build_lcs_table(from, to);
+        let mut diffs: Vec<_> = vec![];
+        make_diffs(&table, from, to, from.len(), to.len(), &mut diffs);
+        diffs
+    }
+}
+
+/// Myers' greedy algorithm (Algorithm::Myers)
+#[derive(Debug, Clone, Copy)]
+pub struct Myers;
+
+impl<T: PartialEq> DiffAlgorithm<T> for Myers {
+    fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+        myers_diffs(from, to)
+    }
+}
+
+/// Myers' linear space algorithm (Algorithm::LinearMyers)
+#[derive(Debug, Clone, Copy)]
+pub struct LinearMyers;
+
+impl<T: PartialEq> DiffAlgorithm<T> for LinearMyers {
+    fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+        linear_myers_diffs(from, to)
+    }
+}
+
+/// Patience diff (Algorithm::Patience)
+#[derive(Debug, Clone, Copy)]
+pub struct Patience;
+
+impl<T: Eq + Hash> DiffAlgorithm<T> for Patience {
+    fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+        patience_diffs(from, to)
+    }
+}
+
+/// Histogram diff (Algorithm::Histogram)
+#[derive(Debug, Clone, Copy)]
+pub struct Histogram;
+
+impl<T: Eq + Hash> DiffAlgorithm<T> for Histogram {
+    fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+        histogram_diffs(from, to)
+    }
+}
+
+impl<T: Eq + Hash> DiffAlgorithm<T> for Algorithm {
+    fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+        match *self {
+            Algorithm::Lcs => Lcs.diffs(from, to),
+            Algorithm::Myers => Myers.diffs(from, to),
+            Algorithm::LinearMyers => LinearMyers.diffs(from, to),
+            Algorithm::Patience => Patience.diffs(from, to),
+            Algorithm::Histogram => Histogram.diffs(from, to),
+        }
+    }
+}
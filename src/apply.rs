@@ -0,0 +1,328 @@
+use diffitem::DiffItem;
+use parse::{PatchHunk, PatchLine};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+/// Why an edit script couldn't be applied by patch_all or unpatch_all
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// An edit refers to lines past the end of the input
+    OutOfBounds {
+        /// The position of the edit in the script
+        edit: usize,
+        /// The number of lines the edit needs the input to have
+        needed: usize,
+        /// The number of lines the input has
+        len: usize,
+    },
+    /// A line the edit deletes or changes isn't what the edit recorded
+    Mismatch {
+        /// The position of the edit in the script
+        edit: usize,
+        /// The 1-based number of the line in the input
+        line: usize,
+    },
+    /// An edit starts before the end of the edit preceding it; edits must be
+    /// in order and can't overlap
+    OutOfOrder {
+        /// The position of the edit in the script
+        edit: usize,
+    },
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            PatchError::OutOfBounds { edit, needed, len } => {
+                write!(f,
+                       "edit {} needs {} lines, but the input only has {}",
+                       edit + 1,
+                       needed,
+                       len)
+            }
+            PatchError::Mismatch { edit, line } => {
+                write!(f, "edit {} doesn't match line {} of the input", edit + 1, line)
+            }
+            PatchError::OutOfOrder { edit } => {
+                write!(f, "edit {} overlaps or comes before the edit preceding it", edit + 1)
+            }
+        }
+    }
+}
+
+impl Error for PatchError {}
+
+/// Applies a whole edit script to a slice in one pass.
+///
+/// The line numbers of every DiffItem refer to the original input, as
+/// produced by diff. Each edit is checked before it is applied: the lines it
+/// deletes or changes must be in the input and equal to the ones it
+/// recorded, and it must come after the edit before it.
+///
+/// # Example
+/// ```
+/// use diff::{diff, patch_all, PatchError};
+///
+/// let from = vec!["a", "b", "c", "d"];
+/// let to = vec!["x", "a", "c", "y"];
+///
+/// let changes = diff(&from, &to);
+/// assert_eq!(patch_all(&from, &changes), Ok(to.clone()));
+///
+/// // the script doesn't fit other input
+/// let other = vec!["a", "c"];
+/// assert_eq!(patch_all(&other, &changes), Err(PatchError::Mismatch { edit: 1, line: 2 }));
+/// ```
+pub fn patch_all<'a, T>(input: &[T], diffs: &[DiffItem<'a, T>]) -> Result<Vec<T>, PatchError>
+    where T: Clone + PartialEq
+{
+    let mut patched = Vec::with_capacity(input.len());
+    let mut line = 0;
+    for (edit, item) in diffs.iter().enumerate() {
+        let range = item.from_range();
+        if range.start < line {
+            return Err(PatchError::OutOfOrder { edit });
+        }
+        if range.end > input.len() {
+            return Err(PatchError::OutOfBounds {
+                edit,
+                needed: range.end,
+                len: input.len(),
+            });
+        }
+        let removed = item.removed().iter().zip(&input[range.clone()]);
+        for (offset, (expected, actual)) in removed.enumerate() {
+            if expected != actual {
+                return Err(PatchError::Mismatch {
+                    edit,
+                    line: range.start + offset + 1,
+                });
+            }
+        }
+
+        patched.extend_from_slice(&input[line..range.start]);
+        patched.extend_from_slice(item.inserted());
+        line = range.end;
+    }
+    patched.extend_from_slice(&input[line..]);
+    Ok(patched)
+}
+
+/// Undoes an edit script: given the "to" that diff(from, to) was computed
+/// against, reconstructs "from". This applies the inverse of every edit,
+/// with the same checks as patch_all.
+///
+/// # Example
+/// ```
+/// use diff::{diff, unpatch_all};
+///
+/// let from = vec!["a", "b", "c", "d"];
+/// let to = vec!["x", "a", "c", "y"];
+///
+/// let changes = diff(&from, &to);
+/// assert_eq!(unpatch_all(&to, &changes), Ok(from.clone()));
+/// ```
+pub fn unpatch_all<'a, T>(output: &[T], diffs: &[DiffItem<'a, T>]) -> Result<Vec<T>, PatchError>
+    where T: Clone + PartialEq
+{
+    let inverted: Vec<_> = diffs.iter().map(DiffItem::invert).collect();
+    patch_all(output, &inverted)
+}
+
+/// What happened to one hunk in apply_hunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkOutcome {
+    /// The hunk matched exactly where it said
+    Applied,
+    /// The hunk matched exactly, this many lines after (or before, if
+    /// negative) where it said
+    Offset(isize),
+    /// The hunk matched after dropping up to fuzz context lines from its
+    /// start and end, offset lines from where it said
+    Fuzz {
+        /// The most context lines dropped from either end
+        fuzz: usize,
+        /// Lines after (or before, if negative) where the hunk said
+        offset: isize,
+    },
+    /// The hunk didn't match anywhere and was skipped
+    Rejected,
+}
+
+/// The result of apply_hunks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedHunks {
+    /// The patched lines
+    pub lines: Vec<String>,
+    /// What happened to each hunk, in order
+    pub outcomes: Vec<HunkOutcome>,
+    /// Whether the patched lines end without a newline, as the last hunk
+    /// applied at the very end of the input says, or None if no hunk was
+    /// and the input ends as it did
+    pub missing_newline: Option<bool>,
+}
+
+impl AppliedHunks {
+    /// The positions of the hunks that were rejected
+    pub fn rejected(&self) -> Vec<usize> {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter(|&(_, outcome)| *outcome == HunkOutcome::Rejected)
+            .map(|(n, _)| n)
+            .collect()
+    }
+}
+
+/// Applies parsed hunks, such as those read by parse_unified, to lines of
+/// text that may have changed since the diff was made.
+///
+/// Like patch, each hunk is looked for where it says it goes, shifted by
+/// the offset the hunk before it was found at. If the lines it expects (its
+/// context and removed lines) aren't there, the nearest place they are is
+/// used instead. As in GNU patch, only its leading context may overlap the
+/// lines an earlier hunk covered, as GNU diff's hunks do when a change left
+/// out for -B or -I falls between them. If they aren't anywhere, up to fuzz
+/// context lines are dropped from each end of the hunk and the search is
+/// repeated. Hunks that still don't match are skipped, leaving those lines
+/// as they were.
+///
+/// # Example
+/// ```
+/// use diff::{apply_hunks, parse_unified, HunkOutcome};
+///
+/// let files = parse_unified("--- a\n+++ b\n@@ -2 +2 @@\n-an\n+another\n").unwrap();
+/// let applied = apply_hunks(&["this", "is", "an", "example"], &files[0].hunks, 0);
+/// assert_eq!(applied.lines, vec!["this", "is", "another", "example"]);
+/// assert_eq!(applied.outcomes, vec![HunkOutcome::Offset(1)]);
+/// ```
+pub fn apply_hunks<S: AsRef<str>>(input: &[S], hunks: &[PatchHunk], fuzz: usize) -> AppliedHunks {
+    let input: Vec<&str> = input.iter().map(AsRef::as_ref).collect();
+    let mut applied = AppliedHunks {
+        lines: Vec::with_capacity(input.len()),
+        outcomes: Vec::new(),
+        missing_newline: None,
+    };
+    let mut line = 0;
+    let mut offset = 0;
+    for hunk in hunks {
+        let guess = hunk.from.start as isize + offset;
+        let (leading, trailing) = context_lengths(hunk);
+        let found = (0..fuzz.min(leading.max(trailing)) + 1)
+            .filter_map(|fuzz| find_hunk(&input, hunk, fuzz, line, guess))
+            .next();
+        let found = match found {
+            Some(found) => found,
+            None => {
+                applied.outcomes.push(HunkOutcome::Rejected);
+                continue;
+            }
+        };
+
+        offset = found.lines.start as isize - found.prefix as isize - hunk.from.start as isize;
+        applied.outcomes.push(match (found.fuzz, offset) {
+            (0, 0) => HunkOutcome::Applied,
+            (0, offset) => HunkOutcome::Offset(offset),
+            (fuzz, offset) => HunkOutcome::Fuzz { fuzz, offset },
+        });
+        // with none of its end cut off, the hunk says how the file ends
+        if found.lines.end == input.len() && found.suffix == 0 {
+            applied.missing_newline = Some(hunk.to_missing_newline);
+        }
+        // leading context lines an earlier hunk already wrote are skipped
+        let overlap = line.saturating_sub(found.lines.start);
+        let replacement = hunk.to_lines();
+        let replacement = &replacement[found.prefix + overlap..replacement.len() - found.suffix];
+        applied.lines
+            .extend(input[line..found.lines.start.max(line)].iter().map(|line| line.to_string()));
+        applied.lines.extend(replacement.iter().map(|line| line.to_string()));
+        line = found.lines.end;
+    }
+    applied.lines.extend(input[line..].iter().map(|line| line.to_string()));
+    applied
+}
+
+/// Where find_hunk found a hunk
+struct Found {
+    /// The lines of the input the hunk matched
+    lines: Range<usize>,
+    /// The number of context lines dropped from the start of the hunk
+    prefix: usize,
+    /// The number of context lines dropped from the end of the hunk
+    suffix: usize,
+    fuzz: usize,
+}
+
+/// Looks for the lines a hunk expects, starting at or after the line
+/// "after" apart from leading context, the way GNU patch does. The place nearest to where the hunk
+/// should start (guess) is used, trying later lines first on a tie.
+///
+/// A hunk with less context at one end than the other must be at that end
+/// of the file, so fuzz comes off the longer end first.
+fn find_hunk(input: &[&str], hunk: &PatchHunk, fuzz: usize, after: usize, guess: isize)
+             -> Option<Found> {
+    let expected = hunk.from_lines();
+    let (leading, trailing) = context_lengths(hunk);
+    let context = leading.max(trailing);
+    let prefix = fuzz as isize + leading as isize - context as isize;
+    let suffix = fuzz as isize + trailing as isize - context as isize;
+    let len = input.len() as isize;
+    let matches = |start: isize, prefix: isize, suffix: isize| {
+        let pattern = &expected[prefix as usize..expected.len() - suffix as usize];
+        let window = start + prefix;
+        let overlap = leading as isize - prefix;
+        if start < 0 || window + overlap < after as isize || window + pattern.len() as isize > len {
+            return None;
+        }
+        let window = window as usize;
+        if input[window..window + pattern.len()] != *pattern {
+            return None;
+        }
+        Some(Found {
+            lines: window..window + pattern.len(),
+            prefix: prefix as usize,
+            suffix: suffix as usize,
+            fuzz,
+        })
+    };
+
+    if expected.is_empty() {
+        let start = guess.max(after as isize).min(len);
+        return matches(start, 0, 0);
+    }
+    if prefix < 0 && hunk.from.start == 0 {
+        // only the start of the file will do, or all of it
+        if suffix < 0 && expected.len() != input.len() {
+            return None;
+        }
+        return matches(0, 0, suffix.max(0));
+    }
+    let prefix = prefix.max(0);
+    if suffix < 0 {
+        // only the end of the file will do
+        return matches(len - expected.len() as isize, prefix, 0);
+    }
+
+    let mut distance = 0;
+    while guess + distance <= len || guess - distance >= 0 {
+        for &start in &[guess + distance, guess - distance] {
+            if let Some(found) = matches(start, prefix, suffix) {
+                return Some(found);
+            }
+        }
+        distance += 1;
+    }
+    None
+}
+
+/// The number of context lines at the start and end of a hunk
+fn context_lengths(hunk: &PatchHunk) -> (usize, usize) {
+    let leading = hunk.lines.iter().take_while(|line| is_context(line)).count();
+    let trailing = hunk.lines.iter().rev().take_while(|line| is_context(line)).count();
+    (leading, trailing)
+}
+
+fn is_context(line: &PatchLine) -> bool {
+    matches!(*line, PatchLine::Context(_))
+}
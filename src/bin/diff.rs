@@ -1,54 +1,276 @@
-/// diff.rs
-///
-/// A command line diff utility
-///
-/// Given two text files, prints out the differences between them
-/// and edit scripts to show how to make the first look like the second
-///
-/// Usage: diff from.txt to.txt
-///
+//! diff.rs
+//!
+//! A command line diff utility
+//!
+//! Given two text files, prints out the differences between them
+//! and edit scripts to show how to make the first look like the second
+//!
+//! Usage: diff [OPTIONS] from.txt to.txt
+//!
+//! --steps       print each edit applied to the first file, in color
+//! -u, -U N      print a unified diff with 3 (or N) lines of context
+//! -c, -C N      print a context diff with 3 (or N) lines of context
+//! -e, --ed      print an ed script
+//! -n, --rcs     print an RCS format diff
+//! -y, --side-by-side
+//!               print the files in two columns
+//! -W N, --width N, --width=N
+//!               make side by side output N columns wide (default 130)
+//! --left-column print unchanged lines only in the left column
+//! --suppress-common-lines
+//!               leave unchanged lines out of side by side output
+//! --word-diff[=plain|color]
+//!               print a unified diff with changed words marked inline, as
+//!               [-removed-]{+added+} or in color
+//! --format=json print the changes as JSON (see diff::write_json)
+//! -b, --ignore-space-change
+//!               ignore changes in the amount of whitespace
+//! -w, --ignore-all-space
+//!               ignore all whitespace
+//! --ignore-trailing-space
+//!               ignore whitespace at the ends of lines
+//! -B, --ignore-blank-lines
+//!               ignore changes that only add or remove blank lines
+//! -i, --ignore-case
+//!               ignore differences in case
+//! -I RE, --ignore-matching-lines=RE
+//!               ignore changes whose lines all match the regular expression
+//!               RE (can be given more than once)
+//! --patience    use the patience diff algorithm
+//! --histogram   use the histogram diff algorithm
+//!
+//! The modification times in unified and context diff headers are shown in
+//! UTC, not the local time zone.
+//!
+//! Trouble, such as a bad option or a file that can't be read, is reported
+//! with exit status 2.
+//!
 
 extern crate diff;
-use diff::{diff, pretty_print};
-use std::io::{Read, BufReader, BufRead, stdout};
+extern crate regex;
+use diff::{diff_lines, diff_lines_marked, pretty_print, write_unified_file, write_unified_header,
+           write_context_file, write_context_header, write_ed_script, write_rcs,
+           write_side_by_side_marked, write_word_diff, write_json, Algorithm, DiffOptions,
+           FileHeader, MissingNewline, SideBySideOptions, WordStyle, Whitespace};
+use regex::Regex;
+use std::io::{self, Read, stdout};
 use std::env;
 use std::fs::File;
+use std::process;
+
+/// How the differences are printed
+enum Format {
+    /// The classic diff format, DiffItem's Display
+    Normal,
+    /// Each edit applied to the first file, in color
+    Steps,
+    /// Unified diff with the given number of context lines
+    Unified(usize),
+    /// Context diff with the given number of context lines
+    Context(usize),
+    /// An ed script
+    Ed,
+    /// RCS format
+    Rcs,
+    /// Two columns
+    SideBySide,
+    /// Unified diff hunks with changes marked word by word
+    WordDiff(WordStyle),
+    /// JSON, for other programs to read
+    Json,
+}
 
 fn main() {
-    let args: Vec<_> = env::args().collect();
-    if args.len() < 3 {
-        panic!("diff requires two paths to text files as arguments");
-    }
-    let file_a = File::open(&args[1]).unwrap();
-    let file_b = File::open(&args[2]).unwrap();
+    let mut paths: Vec<String> = Vec::new();
+    let mut format = Format::Normal;
+    let mut options = DiffOptions::default();
+    let mut patterns = Vec::new();
+    let mut side_by_side = SideBySideOptions::default();
 
-    let lines_a = read_lines(BufReader::new(file_a));
-    let lines_b = read_lines(BufReader::new(file_b));
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--steps" => format = Format::Steps,
+            "-u" | "--unified" => format = Format::Unified(3),
+            "-U" => format = Format::Unified(parse_count(&arg, args.next())),
+            "-c" | "--context" => format = Format::Context(3),
+            "-C" => format = Format::Context(parse_count(&arg, args.next())),
+            "-e" | "--ed" => format = Format::Ed,
+            "-n" | "--rcs" => format = Format::Rcs,
+            "-y" | "--side-by-side" => format = Format::SideBySide,
+            "-W" | "--width" => side_by_side.width = parse_count(&arg, args.next()),
+            "--left-column" => side_by_side.left_column = true,
+            "--suppress-common-lines" => side_by_side.suppress_common_lines = true,
+            "--word-diff" | "--word-diff=plain" => format = Format::WordDiff(WordStyle::Markup),
+            "--word-diff=color" => format = Format::WordDiff(WordStyle::Color),
+            "--format=json" => format = Format::Json,
+            // the option that ignores the most wins, whatever the order
+            "-b" | "--ignore-space-change" => {
+                options.whitespace = options.whitespace.max(Whitespace::IgnoreAmount)
+            }
+            "-w" | "--ignore-all-space" => {
+                options.whitespace = options.whitespace.max(Whitespace::IgnoreAll)
+            }
+            "--ignore-trailing-space" => {
+                options.whitespace = options.whitespace.max(Whitespace::IgnoreTrailing)
+            }
+            "-B" | "--ignore-blank-lines" => options.ignore_blank_lines = true,
+            "-i" | "--ignore-case" => options.ignore_case = true,
+            "-I" => patterns.push(required(&arg, args.next())),
+            "--patience" => options.algorithm = Some(Algorithm::Patience),
+            "--histogram" => options.algorithm = Some(Algorithm::Histogram),
+            _ if arg.starts_with("--unified=") => {
+                format = Format::Unified(parse_count("--unified", Some(arg[10..].to_string())))
+            }
+            _ if arg.starts_with("-U") => {
+                format = Format::Unified(parse_count("-U", Some(arg[2..].to_string())))
+            }
+            _ if arg.starts_with("--context=") => {
+                format = Format::Context(parse_count("--context", Some(arg[10..].to_string())))
+            }
+            _ if arg.starts_with("-C") => {
+                format = Format::Context(parse_count("-C", Some(arg[2..].to_string())))
+            }
+            _ if arg.starts_with("--width=") => {
+                side_by_side.width = parse_count("--width", Some(arg[8..].to_string()))
+            }
+            _ if arg.starts_with("-W") => {
+                side_by_side.width = parse_count("-W", Some(arg[2..].to_string()))
+            }
+            _ if arg.starts_with("--ignore-matching-lines=") => {
+                patterns.push(arg[24..].to_string())
+            }
+            _ if arg.starts_with("-I") => patterns.push(arg[2..].to_string()),
+            _ if arg.starts_with('-') && arg.len() > 1 => fail(&format!("unknown option {}", arg)),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() != 2 {
+        fail("diff requires two paths to text files as arguments");
+    }
+    let (lines_a, missing_newline_a) = read_file(&paths[0]);
+    let (lines_b, missing_newline_b) = read_file(&paths[1]);
+    options.missing_newline = MissingNewline {
+        from: missing_newline_a,
+        to: missing_newline_b,
+    };
 
-    let diffs = diff(&lines_a, &lines_b);
+    for pattern in &patterns {
+        match Regex::new(pattern) {
+            Ok(regex) => options.ignore_matching_lines.push(regex),
+            Err(error) => fail(&format!("invalid regular expression {}: {}", pattern, error)),
+        }
+    }
+    // whether changes -B and -I leave out are kept depends on the hunks
+    options.context = match format {
+        Format::Unified(context) | Format::Context(context) => context,
+        Format::WordDiff(_) => 3,
+        _ => 0,
+    };
+    if let Format::SideBySide = format {
+        // both files are shown in full, so the changes -B and -I leave out
+        // are needed to line them up
+        let diffs = diff_lines_marked(&lines_a, &lines_b, &options);
+        write_side_by_side_marked(stdout(), &lines_a, &lines_b, &diffs, &side_by_side).unwrap();
+        return;
+    }
+    let diffs = diff_lines(&lines_a, &lines_b, &options);
 
-    if args.len() == 4 && args[3] == "--steps" {
-        let mut i = 0;
-        for diff in &diffs {
-            i += 1;
-            println!("\nEdit {}:", i);
-            pretty_print(stdout(), &lines_a, diff);
+    match format {
+        Format::Normal => {
+            for diff in &diffs {
+                print!("{}", *diff);
+            }
+        }
+        Format::Steps => {
+            let mut i = 0;
+            for diff in &diffs {
+                i += 1;
+                println!("\nEdit {}:", i);
+                pretty_print(stdout(), &lines_a, diff);
+            }
+        }
+        Format::Unified(context) => {
+            if !diffs.is_empty() {
+                write_unified_header(stdout(), &header(&paths[0]), &header(&paths[1])).unwrap();
+                write_unified_file(stdout(),
+                                   &lines_a,
+                                   &lines_b,
+                                   &diffs,
+                                   context,
+                                   options.missing_newline)
+                    .unwrap();
+            }
+        }
+        Format::Context(context) => {
+            if !diffs.is_empty() {
+                write_context_header(stdout(), &header(&paths[0]), &header(&paths[1])).unwrap();
+                write_context_file(stdout(),
+                                   &lines_a,
+                                   &lines_b,
+                                   &diffs,
+                                   context,
+                                   options.missing_newline)
+                    .unwrap();
+            }
         }
-    } else {
-        for diff in &diffs {
-            print!("{}", *diff);
+        Format::Ed => write_ed_script(stdout(), &diffs).unwrap(),
+        Format::Rcs => write_rcs(stdout(), &diffs).unwrap(),
+        Format::SideBySide => unreachable!(),
+        Format::WordDiff(style) => {
+            if !diffs.is_empty() {
+                write_unified_header(stdout(), &header(&paths[0]), &header(&paths[1])).unwrap();
+                write_word_diff(stdout(), &lines_a, &lines_b, &diffs, 3, style).unwrap();
+            }
         }
+        Format::Json => write_json(stdout(), &paths[0], &paths[1], &diffs).unwrap(),
     }
 
 }
 
-/// Read from a reader to a Vec<String> of lines
-fn read_lines<R: Read>(reader: R) -> Vec<String> {
-    let mut result: Vec<String> = Vec::new();
-    let mut lines = BufReader::new(reader).lines();
+/// The value of an option like -I
+fn required(option: &str, value: Option<String>) -> String {
+    value.unwrap_or_else(|| fail(&format!("{} requires an argument", option)))
+}
+
+/// Parse the number given to an option like -U or -W
+fn parse_count(option: &str, value: Option<String>) -> usize {
+    match value.map(|value| value.parse()) {
+        Some(Ok(count)) => count,
+        _ => fail(&format!("{} requires a number", option)),
+    }
+}
+
+/// The header line for a file, with its modification time if available
+fn header(path: &str) -> FileHeader {
+    FileHeader::for_file(path).unwrap_or_else(|_| FileHeader::new(path))
+}
 
-    while let Some(Ok(line)) = lines.next() {
-        result.push(line.to_string());
+/// Read the lines of a file as read_lines does, failing if it can't be
+/// opened or isn't UTF-8 text
+fn read_file(path: &str) -> (Vec<String>, bool) {
+    match File::open(path).and_then(read_lines) {
+        Ok(lines) => lines,
+        Err(error) => fail(&format!("can't read {}: {}", path, error)),
     }
-    result
+}
+
+/// Read from a reader to a Vec<String> of lines, split on "\n" only so
+/// any "\r" is kept, and whether the last line has no newline
+fn read_lines<R: Read>(mut reader: R) -> io::Result<(Vec<String>, bool)> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let missing_newline = !text.is_empty() && !text.ends_with('\n');
+    let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    if !missing_newline {
+        // the newline ends the last line rather than starting another
+        lines.pop();
+    }
+    Ok((lines, missing_newline))
+}
+
+/// Prints an error and exits with status 2, as diff does for trouble
+fn fail(message: &str) -> ! {
+    eprintln!("rust-diff: {}", message);
+    process::exit(2)
 }
@@ -0,0 +1,120 @@
+//! diff3.rs
+//!
+//! A command line three-way merge utility
+//!
+//! Merges the changes from OLDFILE to MYFILE and from OLDFILE to YOURFILE,
+//! printing the result with conflicts between markers, like diff3 -m
+//!
+//! Usage: diff3 [OPTIONS] MYFILE OLDFILE YOURFILE
+//!
+//! -L LABEL      use LABEL instead of a file name in conflict markers; give
+//!               it up to three times, for MYFILE, OLDFILE and YOURFILE
+//!
+//! The exit status is 1 if there were conflicts and 2 if there was trouble.
+//!
+
+extern crate diff;
+use diff::{merge, write_merge, MergeChunk, MergeLabels};
+use std::env;
+use std::fs::File;
+use std::io::{Read, Write, stdout};
+use std::process;
+use std::ptr;
+
+fn main() {
+    let mut paths: Vec<String> = Vec::new();
+    let mut labels: Vec<String> = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-m" | "--merge" => {}
+            "-L" | "--label" => {
+                match args.next() {
+                    Some(label) => labels.push(label),
+                    None => fail(&format!("{} requires a label", arg)),
+                }
+            }
+            _ if arg.starts_with("--label=") => labels.push(arg[8..].to_string()),
+            _ if arg.starts_with('-') && arg.len() > 1 => fail(&format!("unknown option {}", arg)),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() != 3 {
+        fail("diff3 requires three paths to text files as arguments");
+    }
+    if labels.len() > 3 {
+        fail("too many labels");
+    }
+    let label = |n: usize| labels.get(n).unwrap_or(&paths[n]).as_str();
+
+    let (ours, ours_missing_newline) = read_file(&paths[0]);
+    let (base, base_missing_newline) = read_file(&paths[1]);
+    let (theirs, theirs_missing_newline) = read_file(&paths[2]);
+
+    let chunks = merge(&base, &ours, &theirs);
+    let labels = MergeLabels {
+        ours: label(0),
+        base: label(1),
+        theirs: label(2),
+    };
+    let mut output = Vec::new();
+    write_merge(&mut output, &chunks, &labels).unwrap();
+
+    // A merge that ends with the last line of a file missing its newline
+    // leaves it missing too
+    let last_line = chunks.last().and_then(MergeChunk::resolved).and_then(<[String]>::last);
+    let missing_newline = [(&ours, ours_missing_newline),
+                           (&base, base_missing_newline),
+                           (&theirs, theirs_missing_newline)]
+        .iter()
+        .any(|&(lines, missing)| {
+            missing && match (last_line, lines.last()) {
+                (Some(merged), Some(line)) => ptr::eq(merged, line),
+                _ => false,
+            }
+        });
+    if missing_newline {
+        output.pop();
+    }
+    if let Err(error) = stdout().write_all(&output) {
+        fail(&format!("can't write output: {}", error));
+    }
+
+    if chunks.iter().any(MergeChunk::is_conflict) {
+        process::exit(1);
+    }
+}
+
+/// Read the lines of a file, failing if it can't be read, and whether its
+/// last line is missing a newline
+fn read_file(path: &str) -> (Vec<String>, bool) {
+    match File::open(path).and_then(read_text) {
+        Ok(text) => read_lines(&text),
+        Err(error) => fail(&format!("can't read {}: {}", path, error)),
+    }
+}
+
+/// Read all of a reader as UTF-8 text
+fn read_text<R: Read>(mut reader: R) -> std::io::Result<String> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Split text into lines on "\n" alone, so a "\r" before it is kept, and
+/// say whether the last line is missing its newline
+fn read_lines(text: &str) -> (Vec<String>, bool) {
+    let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    let missing_newline = !text.is_empty() && !text.ends_with('\n');
+    if !missing_newline {
+        lines.pop();
+    }
+    (lines, missing_newline)
+}
+
+/// Prints an error and exits with status 2, as diff3 does for trouble
+fn fail(message: &str) -> ! {
+    eprintln!("rust-diff3: {}", message);
+    process::exit(2)
+}
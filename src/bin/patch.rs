@@ -0,0 +1,279 @@
+//! patch.rs
+//!
+//! A command line patch utility
+//!
+//! Reads a unified or normal format diff and applies it to the files on
+//! disk that it describes
+//!
+//! Usage: patch [OPTIONS] [FILE [PATCHFILE]]
+//!
+//! The patch is read from PATCHFILE, or standard input. A normal format diff
+//! doesn't name its file, so FILE must be given; for a unified diff it
+//! overrides the file names in the patch.
+//!
+//! -i PATCHFILE  read the patch from PATCHFILE
+//! -p N          strip the first N directories from file names in the patch
+//!               (by default only the last part of the name is used)
+//! -R, --reverse undo the patch instead of applying it
+//! -F N, --fuzz=N
+//!               ignore up to N lines of context at each end of a hunk that
+//!               doesn't match exactly (default 2)
+//! --dry-run     report what would happen without changing any files
+//! -b, --backup  save the original of each patched file as FILE.orig
+//!
+//! Hunks that have moved are found by searching the file for their lines.
+//! Hunks that don't apply are saved to FILE.rej, and the exit status is 1.
+//!
+
+extern crate diff;
+use diff::{apply_hunks, parse_normal, parse_unified, FilePatch, HunkOutcome};
+use std::env;
+use std::fs::{self, File};
+use std::io::{Read, Write, stdin};
+use std::path::Path;
+use std::process;
+
+/// The name the patch uses for a file that doesn't exist on one side
+const DEV_NULL: &str = "/dev/null";
+
+/// Command line settings
+struct Options {
+    strip: Option<usize>,
+    fuzz: usize,
+    reverse: bool,
+    dry_run: bool,
+    backup: bool,
+}
+
+fn main() {
+    let mut paths: Vec<String> = Vec::new();
+    let mut patch_path = None;
+    let mut options = Options {
+        strip: None,
+        fuzz: 2,
+        reverse: false,
+        dry_run: false,
+        backup: false,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--input" => patch_path = Some(required(&arg, args.next())),
+            "-p" => options.strip = Some(parse_count(&arg, args.next())),
+            "-F" => options.fuzz = parse_count(&arg, args.next()),
+            "-R" | "--reverse" => options.reverse = true,
+            "--dry-run" => options.dry_run = true,
+            "-b" | "--backup" => options.backup = true,
+            _ if arg.starts_with("--input=") => patch_path = Some(arg[8..].to_string()),
+            _ if arg.starts_with("--strip=") => {
+                options.strip = Some(parse_count("--strip", Some(arg[8..].to_string())))
+            }
+            _ if arg.starts_with("--fuzz=") => {
+                options.fuzz = parse_count("--fuzz", Some(arg[7..].to_string()))
+            }
+            _ if arg.starts_with("-F") => {
+                options.fuzz = parse_count("-F", Some(arg[2..].to_string()))
+            }
+            _ if arg.starts_with("-p") => {
+                options.strip = Some(parse_count("-p", Some(arg[2..].to_string())))
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => fail(&format!("unknown option {}", arg)),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.len() > 1 {
+        patch_path = Some(paths.remove(1));
+    }
+    let target = paths.pop();
+
+    let mut text = String::new();
+    let read = match patch_path {
+        Some(ref path) => File::open(path).and_then(|mut file| file.read_to_string(&mut text)),
+        None => stdin().read_to_string(&mut text),
+    };
+    if let Err(error) = read {
+        fail(&format!("can't read the patch: {}", error));
+    }
+
+    let mut files = parse_unified(&text).unwrap_or_else(|error| fail(&error.to_string()));
+    if files.is_empty() {
+        let hunks = parse_normal(&text).unwrap_or_else(|error| fail(&error.to_string()));
+        if target.is_none() {
+            fail("a normal format diff needs the name of the file to patch");
+        }
+        files.push(FilePatch {
+            from_path: String::new(),
+            to_path: String::new(),
+            hunks,
+        });
+    }
+
+    let mut failed = false;
+    for file in &files {
+        let path = match target {
+            Some(ref target) => target.clone(),
+            None => choose_path(file, &options),
+        };
+        failed |= patch_file(&path, file, &options);
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// Applies a file's hunks to the file at path, returning whether any of
+/// them failed
+fn patch_file(path: &str, file: &FilePatch, options: &Options) -> bool {
+    let original = match fs::read_to_string(path) {
+        Ok(original) => Some(original),
+        Err(_) if !Path::new(path).exists() => None,
+        Err(error) => fail(&format!("can't read {}: {}", path, error)),
+    };
+    let content = original.clone().unwrap_or_default();
+    // split on "\n" only, so "\r\n" line endings are kept
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    if content.is_empty() || content.ends_with('\n') {
+        lines.pop();
+    }
+
+    let hunks: Vec<_> = if options.reverse {
+        file.hunks.iter().map(|hunk| hunk.invert()).collect()
+    } else {
+        file.hunks.clone()
+    };
+    let applied = apply_hunks(&lines, &hunks, options.fuzz);
+    let rejected = applied.rejected();
+
+    println!("{} file {}", if options.dry_run { "checking" } else { "patching" }, path);
+    for (n, (hunk, outcome)) in hunks.iter().zip(&applied.outcomes).enumerate() {
+        let (fuzz, offset) = match *outcome {
+            HunkOutcome::Applied => (0, 0),
+            HunkOutcome::Offset(offset) => (0, offset),
+            HunkOutcome::Fuzz { fuzz, offset } => (fuzz, offset),
+            HunkOutcome::Rejected => {
+                println!("Hunk #{} FAILED at {}.", n + 1, hunk.from.start + 1);
+                continue;
+            }
+        };
+        if *outcome != HunkOutcome::Applied {
+            print!("Hunk #{} succeeded at {}", n + 1, hunk.from.start as isize + offset + 1);
+            if fuzz > 0 {
+                print!(" with fuzz {}", fuzz);
+            }
+            if offset != 0 {
+                print!(" (offset {} line{})", offset, if offset.abs() == 1 { "" } else { "s" });
+            }
+            println!(".");
+        }
+    }
+    let rejects = format!("{}.rej", path);
+    if !rejected.is_empty() {
+        println!("{} out of {} hunk{} FAILED{}",
+                 rejected.len(),
+                 hunks.len(),
+                 if hunks.len() == 1 { "" } else { "s" },
+                 if options.dry_run {
+                     String::new()
+                 } else {
+                     format!(" -- saving rejects to file {}", rejects)
+                 });
+    }
+    if options.dry_run {
+        return !rejected.is_empty();
+    }
+
+    if options.backup {
+        write_file(&format!("{}.orig", path),
+                   original.as_ref().map_or("", String::as_str));
+    }
+    let deleted = if options.reverse { &file.from_path } else { &file.to_path };
+    if applied.lines.is_empty() && deleted == DEV_NULL {
+        if original.is_some() {
+            fs::remove_file(path).unwrap_or_else(|error| {
+                fail(&format!("can't remove {}: {}", path, error))
+            });
+        }
+    } else {
+        // the last hunk applied at the end of the file decides how it ends
+        let missing_newline = applied.missing_newline
+            .unwrap_or(!content.is_empty() && !content.ends_with('\n'));
+        let mut patched = applied.lines.join("\n");
+        if !applied.lines.is_empty() && !missing_newline {
+            patched.push('\n');
+        }
+        write_file(path, &patched);
+    }
+
+    if rejected.is_empty() {
+        return false;
+    }
+    let (from_path, to_path) = if options.reverse {
+        (&file.to_path, &file.from_path)
+    } else {
+        (&file.from_path, &file.to_path)
+    };
+    let mut rejects_text = format!("--- {}\n+++ {}\n", from_path, to_path);
+    for &n in &rejected {
+        rejects_text.push_str(&hunks[n].to_string());
+    }
+    write_file(&rejects, &rejects_text);
+    true
+}
+
+/// Picks the file on disk to patch from the names in the patch: the first
+/// one that exists, or the new name if the patch creates the file
+fn choose_path(file: &FilePatch, options: &Options) -> String {
+    let (old, new) = if options.reverse {
+        (&file.to_path, &file.from_path)
+    } else {
+        (&file.from_path, &file.to_path)
+    };
+    let names: Vec<String> = [old, new]
+        .iter()
+        .filter(|name| name.as_str() != DEV_NULL)
+        .map(|name| strip_path(name, options.strip))
+        .collect();
+    match names.iter().find(|name| Path::new(name).exists()) {
+        Some(name) => name.clone(),
+        None => {
+            names.last()
+                .cloned()
+                .unwrap_or_else(|| fail("the patch doesn't name a file"))
+        }
+    }
+}
+
+/// Removes the first count directories from a path, or all of them if
+/// count is None
+fn strip_path(path: &str, count: Option<usize>) -> String {
+    let parts: Vec<&str> = path.split('/').collect();
+    let count = count.unwrap_or(parts.len() - 1).min(parts.len() - 1);
+    parts[count..].join("/")
+}
+
+fn write_file(path: &str, content: &str) {
+    File::create(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .unwrap_or_else(|error| fail(&format!("can't write {}: {}", path, error)))
+}
+
+/// Parse the number given to an option like -p
+fn parse_count(option: &str, value: Option<String>) -> usize {
+    match value.map(|value| value.parse()) {
+        Some(Ok(count)) => count,
+        _ => fail(&format!("{} requires a number", option)),
+    }
+}
+
+/// The value of an option like -i
+fn required(option: &str, value: Option<String>) -> String {
+    value.unwrap_or_else(|| fail(&format!("{} requires a file name", option)))
+}
+
+/// Prints an error and exits with status 2, as patch does for trouble
+/// other than failed hunks
+fn fail(message: &str) -> ! {
+    eprintln!("rust-patch: {}", message);
+    process::exit(2)
+}
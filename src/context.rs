@@ -0,0 +1,151 @@
+use diffitem::DiffItem;
+use header::{FileHeader, context_header};
+use hunks::{hunks, EditScript};
+use options::MissingNewline;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
+use unified::write_line;
+
+/// Writes an edit script in context format (diff -c), with "context"
+/// unchanged lines around every change. Nothing is written if there are no
+/// changes. Call write_context_header first to produce a complete diff.
+///
+/// Each hunk lists the lines of "from" and then the lines of "to". Lines
+/// only in "from" are marked with "-", lines only in "to" with "+", and
+/// lines replaced by a Change with "!" on both sides. A side without any
+/// marked lines is left out.
+///
+/// # Example
+/// ```
+/// use diff::{diff, write_context};
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+///
+/// let mut output = Vec::new();
+/// write_context(&mut output, &from, &to, &diff(&from, &to), 1).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "***************\n*** 2,4 ****\n  is\n! an\n  example\n\
+///             --- 2,4 ----\n  is\n! another\n  example\n");
+/// ```
+pub fn write_context<'a, T, W, S>(writer: W,
+                                  from: &'a [T],
+                                  to: &'a [T],
+                                  diffs: &S,
+                                  context: usize)
+                                  -> io::Result<()>
+    where T: Display,
+          W: Write,
+          S: EditScript<'a, T> + ?Sized
+{
+    write_context_file(writer, from, to, diffs, context, MissingNewline::default())
+}
+
+/// Writes an edit script in context format, like write_context, for files
+/// that may not end with a newline. As with write_unified_file, a last line
+/// without one is followed by "\ No newline at end of file" wherever it is
+/// shown, and the script must treat it as different from one with a newline.
+///
+/// # Example
+/// ```
+/// use diff::{diff_lines, write_context_file, DiffOptions, MissingNewline};
+///
+/// let from = vec!["a", "b"];
+/// let to = vec!["a", "b"];
+///
+/// let missing_newline = MissingNewline { from: true, to: false };
+/// let options = DiffOptions { missing_newline, ..Default::default() };
+/// let diffs = diff_lines(&from, &to, &options);
+/// let mut output = Vec::new();
+/// write_context_file(&mut output, &from, &to, &diffs, 1, missing_newline).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "***************\n*** 1,2 ****\n  a\n! b\n\\ No newline at end of file\n\
+///             --- 1,2 ----\n  a\n! b\n");
+/// ```
+pub fn write_context_file<'a, T, W, S>(mut writer: W,
+                                       from: &'a [T],
+                                       to: &'a [T],
+                                       diffs: &S,
+                                       context: usize,
+                                       missing_newline: MissingNewline)
+                                       -> io::Result<()>
+    where T: Display,
+          W: Write,
+          S: EditScript<'a, T> + ?Sized
+{
+    let from_last = |n: usize| missing_newline.from && n + 1 == from.len();
+    let to_last = |n: usize| missing_newline.to && n + 1 == to.len();
+
+    for hunk in hunks(diffs, from.len(), to.len(), context) {
+        writeln!(writer, "***************")?;
+
+        writeln!(writer, "*** {} ****", context_range(&hunk.from))?;
+        if hunk.items.iter().any(|item| !item.removed().is_empty()) {
+            let mut line = hunk.from.start;
+            for item in hunk.items {
+                let removed = item.from_range();
+                for (n, common) in from.iter().enumerate().take(removed.start).skip(line) {
+                    write_line(&mut writer, "  ", common, from_last(n))?;
+                }
+                let marker = if let DiffItem::Change { .. } = *item { "! " } else { "- " };
+                for (n, deleted) in removed.clone().zip(item.removed()) {
+                    write_line(&mut writer, marker, deleted, from_last(n))?;
+                }
+                line = removed.end;
+            }
+            for (n, common) in from.iter().enumerate().take(hunk.from.end).skip(line) {
+                write_line(&mut writer, "  ", common, from_last(n))?;
+            }
+        }
+
+        writeln!(writer, "--- {} ----", context_range(&hunk.to))?;
+        if hunk.items.iter().any(|item| !item.inserted().is_empty()) {
+            let mut line = hunk.to.start;
+            for item in hunk.items {
+                let inserted = item.to_range();
+                for (n, common) in to.iter().enumerate().take(inserted.start).skip(line) {
+                    write_line(&mut writer, "  ", common, to_last(n))?;
+                }
+                let marker = if let DiffItem::Change { .. } = *item { "! " } else { "+ " };
+                for (n, added) in inserted.clone().zip(item.inserted()) {
+                    write_line(&mut writer, marker, added, to_last(n))?;
+                }
+                line = inserted.end;
+            }
+            for (n, common) in to.iter().enumerate().take(hunk.to.end).skip(line) {
+                write_line(&mut writer, "  ", common, to_last(n))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the "***" and "---" lines that start a context diff
+///
+/// # Example
+/// ```
+/// use diff::{write_context_header, FileHeader};
+///
+/// let mut output = Vec::new();
+/// write_context_header(&mut output, &FileHeader::new("a.txt"), &FileHeader::new("b.txt"))
+///     .unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "*** a.txt\n--- b.txt\n");
+/// ```
+pub fn write_context_header<W: Write>(mut writer: W,
+                                      from: &FileHeader,
+                                      to: &FileHeader)
+                                      -> io::Result<()> {
+    writeln!(writer, "*** {}", context_header(from))?;
+    writeln!(writer, "--- {}", context_header(to))
+}
+
+/// Formats a 0-based range of lines as "first,last", or just the line number
+/// when there is only one. An empty range is shown as the line before it.
+fn context_range(range: &Range<usize>) -> String {
+    if range.end <= range.start + 1 {
+        format!("{}", range.end)
+    } else {
+        format!("{},{}", range.start + 1, range.end)
+    }
+}
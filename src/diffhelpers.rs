@@ -1,4 +1,4 @@
-use std::fmt::{Display, Debug};
+use std::fmt::{Display, Formatter, Error};
 use diffitem::DiffItem;
 
 
@@ -16,18 +16,21 @@ type LCSTable = Vec<Vec<usize>>;
 ///  D -> Add
 ///  C -> Same
 ///
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum EditFlags {
+    /// The item is only in "to"
     Add,
+    /// The item is only in "from"
     Delete,
+    /// The item is in both
     Same,
 }
-impl ToString for EditFlags {
-    fn to_string(&self) -> String {
-        match self {
-            &EditFlags::Add => "+".to_string(),
-            &EditFlags::Delete => "-".to_string(),
-            &EditFlags::Same => "s".to_string(),
+impl Display for EditFlags {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            EditFlags::Add => write!(f, "+"),
+            EditFlags::Delete => write!(f, "-"),
+            EditFlags::Same => write!(f, "s"),
         }
     }
 }
@@ -36,10 +39,8 @@ impl ToString for EditFlags {
 /// Merges consecutive adds/deletes into a single add, delete, or Change
 pub fn convert_to_diffitems<'a, T>(from: &'a [T],
                                    to: &'a [T],
-                                   diffs: &Vec<EditFlags>)
-                                   -> Vec<DiffItem<'a, T>>
-    where T: PartialEq + Display + Debug
-{
+                                   diffs: &[EditFlags])
+                                   -> Vec<DiffItem<'a, T>> {
     let mut result: Vec<DiffItem<'a, T>> = Vec::new();
 
     let mut ind_from = 0; //index of from slice
@@ -47,46 +48,41 @@ pub fn convert_to_diffitems<'a, T>(from: &'a [T],
     let mut edit_tracker: Vec<EditFlags> = vec![];
     let mut s_from = 1; //index of last same line of from slice
     let mut s_to = 1; //index of last same line of to slice
-    let mut num_diffs = 1;
     let diff_length = diffs.len();
 
-    for edit in diffs {
+    for (num_diffs, edit) in diffs.iter().enumerate() {
 
-        edit_tracker.push(edit.clone());
+        edit_tracker.push(*edit);
 
-        match edit {
-            &EditFlags::Same => {
-                if ind_from > 0 && ind_to > 0 {
-                    match check_diff(&mut edit_tracker, s_from, s_to, ind_from, ind_to, from, to) {
-                        Some(x) => result.push(x),
-                        None => {}
-                    }
+        match *edit {
+            EditFlags::Same => {
+                if let Some(x) = check_diff(&mut edit_tracker, s_from, s_to, ind_from, ind_to, from, to) {
+                    result.push(x);
                 }
+                // a run of unchanged items never starts a new edit
+                edit_tracker.clear();
                 ind_from += 1;
                 ind_to += 1;
                 s_from = ind_from + 1;
                 s_to = ind_to + 1;
             }
-            &EditFlags::Delete => {
+            EditFlags::Delete => {
                 ind_from += 1;
-                if num_diffs == diff_length {
-                    match check_diff(&mut edit_tracker, s_from, s_to, ind_from, ind_to, from, to) {
-                        Some(x) => result.push(x),
-                        None => {}
+                if num_diffs + 1 == diff_length {
+                    if let Some(x) = check_diff(&mut edit_tracker, s_from, s_to, ind_from, ind_to, from, to) {
+                        result.push(x);
                     }
                 }
             }
-            &EditFlags::Add => {
+            EditFlags::Add => {
                 ind_to += 1;
-                if num_diffs == diff_length {
-                    match check_diff(&mut edit_tracker, s_from, s_to, ind_from, ind_to, from, to) {
-                        Some(x) => result.push(x),
-                        None => {}
+                if num_diffs + 1 == diff_length {
+                    if let Some(x) = check_diff(&mut edit_tracker, s_from, s_to, ind_from, ind_to, from, to) {
+                        result.push(x);
                     }
                 }
             }
         }
-        num_diffs += 1;
     }
     result
 }
@@ -100,38 +96,36 @@ fn check_diff<'a, T>(edit_tracker: &mut Vec<EditFlags>,
                      j: usize,
                      from: &'a [T],
                      to: &'a [T])
-                     -> Option<DiffItem<'a, T>>
-    where T: PartialEq + Display + Debug
-{
+                     -> Option<DiffItem<'a, T>> {
 
     if !edit_tracker.contains(&EditFlags::Add) && edit_tracker.contains(&EditFlags::Delete) {
         edit_tracker.drain(..);
-        return Some(DiffItem::Delete {
+        Some(DiffItem::Delete {
                         start_from: s1,
                         end_from: i,
                         start_to: s2 - 1,
                         items: &from[s1 - 1..i],
-                    });
+                    })
     } else if !edit_tracker.contains(&EditFlags::Delete) && edit_tracker.contains(&EditFlags::Add) {
         edit_tracker.drain(..);
-        return Some(DiffItem::Add {
+        Some(DiffItem::Add {
                         start_from: s1 - 1,
                         start_to: s2,
                         end_to: j + 1,
                         items: &to[s2 - 1..j],
-                    });
+                    })
     } else if edit_tracker.contains(&EditFlags::Add) && edit_tracker.contains(&EditFlags::Delete) {
         edit_tracker.drain(..);
-        return Some(DiffItem::Change {
+        Some(DiffItem::Change {
                         start_from: s1,
                         start_to: s2,
                         end_from: i,
                         end_to: j,
                         from: &from[s1 - 1..i],
                         to: &to[s2 - 1..j],
-                    });
+                    })
     } else {
-        return None;
+        None
     }
 
 }
@@ -139,23 +133,35 @@ fn check_diff<'a, T>(edit_tracker: &mut Vec<EditFlags>,
 /// Find all the edits necessary to make "from" match "to"
 /// Every edited item is representated by an EditFlag.
 /// There may be multiple consecutive edits, use convert_to_diffitems to merge them.
+///
+/// Walks back through the table from (i, j) one step at a time, so the
+/// depth of the traceback is not limited by the size of the stack.
 pub fn make_diffs<'a, T>(table: &LCSTable,
                          from: &'a [T],
                          to: &'a [T],
-                         i: usize,
-                         j: usize,
+                         mut i: usize,
+                         mut j: usize,
                          diffs: &mut Vec<EditFlags>)
-    where T: PartialEq + Display + Debug
+    where T: PartialEq
 {
+    let start = diffs.len();
 
-    if i > 0 && j > 0 && from[i - 1] == to[j - 1] {
-        make_diffs(table, from, to, i - 1, j - 1, diffs);
-        diffs.push(EditFlags::Same);
-    } else if j > 0 && (i == 0 || table[i][j - 1] >= table[i - 1][j]) {
-        make_diffs(table, from, to, i, j - 1, diffs);
-        diffs.push(EditFlags::Add);
-    } else if i > 0 && (j == 0 || table[i][j - 1] < table[i - 1][j]) {
-        make_diffs(table, from, to, i - 1, j, diffs);
-        diffs.push(EditFlags::Delete);
+    loop {
+        if i > 0 && j > 0 && from[i - 1] == to[j - 1] {
+            diffs.push(EditFlags::Same);
+            i -= 1;
+            j -= 1;
+        } else if j > 0 && (i == 0 || table[i][j - 1] >= table[i - 1][j]) {
+            diffs.push(EditFlags::Add);
+            j -= 1;
+        } else if i > 0 && (j == 0 || table[i][j - 1] < table[i - 1][j]) {
+            diffs.push(EditFlags::Delete);
+            i -= 1;
+        } else {
+            break;
+        }
     }
-}
\ No newline at end of file
+
+    // the edits were found from the end backwards
+    diffs[start..].reverse();
+}
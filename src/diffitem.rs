@@ -1,11 +1,12 @@
-use std::fmt::{Display, Formatter, Error, Debug};
+use std::fmt::{Display, Formatter, Error};
+use std::ops::Range;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
 
 /// The DiffItem enum. Represents an edit action (either Add, Delete, or Change)
 /// Contains the necessary information to make the corresponding change to a slice.
 #[derive(Debug, PartialEq, Eq)]
-pub enum DiffItem<'a, T: 'a>
-    where T: PartialEq + Debug
-{
+pub enum DiffItem<'a, T: 'a> {
     /// Represents an insertion edit
     Add {
         /// The index at which to insert the new items in the "from" slice
@@ -45,8 +46,244 @@ pub enum DiffItem<'a, T: 'a>
     },
 }
 
+impl<'a, T: 'a> DiffItem<'a, T> {
+    /// The items of "from" replaced by this edit, as a 0-based range.
+    /// For an Add this is the empty range at the insertion point.
+    ///
+    /// # Example
+    /// ```
+    /// use diff::diff;
+    ///
+    /// let from = vec!["this", "is", "an", "example"];
+    /// let to = vec!["this", "is", "another", "example"];
+    /// let changes = diff(&from, &to);
+    /// assert_eq!(changes[0].from_range(), 2..3);
+    /// ```
+    pub fn from_range(&self) -> Range<usize> {
+        match *self {
+            DiffItem::Add { start_from, .. } => start_from..start_from,
+            DiffItem::Delete { start_from, end_from, .. } |
+            DiffItem::Change { start_from, end_from, .. } => start_from - 1..end_from,
+        }
+    }
+
+    /// The items of "to" this edit produces, as a 0-based range.
+    /// For a Delete this is the empty range where the items used to be.
+    pub fn to_range(&self) -> Range<usize> {
+        match *self {
+            DiffItem::Add { start_to, items, .. } => start_to - 1..start_to - 1 + items.len(),
+            DiffItem::Delete { start_to, .. } => start_to..start_to,
+            DiffItem::Change { start_to, end_to, .. } => start_to - 1..end_to,
+        }
+    }
+
+    /// The items removed from "from" by this edit
+    pub fn removed(&self) -> &'a [T] {
+        match *self {
+            DiffItem::Add { .. } => &[],
+            DiffItem::Delete { items, .. } => items,
+            DiffItem::Change { from, .. } => from,
+        }
+    }
+
+    /// The items inserted from "to" by this edit
+    pub fn inserted(&self) -> &'a [T] {
+        match *self {
+            DiffItem::Add { items, .. } => items,
+            DiffItem::Delete { .. } => &[],
+            DiffItem::Change { to, .. } => to,
+        }
+    }
+
+    /// The same edit in the other direction, turning "to" back into "from":
+    /// an Add becomes a Delete, a Delete becomes an Add, and a Change swaps
+    /// its sides.
+    ///
+    /// # Example
+    /// ```
+    /// use diff::diff;
+    ///
+    /// let from = vec!["a", "b", "c"];
+    /// let to = vec!["a", "c"];
+    /// let changes = diff(&from, &to);
+    /// assert_eq!(changes[0].invert(), diff(&to, &from)[0]);
+    /// ```
+    pub fn invert(&self) -> DiffItem<'a, T> {
+        match *self {
+            DiffItem::Add { start_from, start_to, end_to, items } => {
+                DiffItem::Delete {
+                    start_from: start_to,
+                    end_from: end_to - 1,
+                    start_to: start_from,
+                    items,
+                }
+            }
+            DiffItem::Delete { start_from, end_from, start_to, items } => {
+                DiffItem::Add {
+                    start_from: start_to,
+                    start_to: start_from,
+                    end_to: end_from + 1,
+                    items,
+                }
+            }
+            DiffItem::Change { start_from, end_from, start_to, end_to, from, to } => {
+                DiffItem::Change {
+                    start_from: start_to,
+                    end_from: end_to,
+                    start_to: start_from,
+                    end_to: end_from,
+                    from: to,
+                    to: from,
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T: Clone> DiffItem<'a, T> {
+    /// Copies the items this edit borrows, so it can outlive the slices it
+    /// was calculated from
+    ///
+    /// # Example
+    /// ```
+    /// use diff::{diff, patch, OwnedDiffItem};
+    ///
+    /// let saved: Vec<OwnedDiffItem<String>> = {
+    ///     let from = vec!["a".to_string(), "b".to_string()];
+    ///     let to = vec!["a".to_string(), "c".to_string()];
+    ///     diff(&from, &to).iter().map(|item| item.to_owned_item()).collect()
+    /// };
+    ///
+    /// let input = vec!["a".to_string(), "b".to_string()];
+    /// assert_eq!(patch(&input, &saved[0].as_diff_item()), vec!["a", "c"]);
+    /// ```
+    pub fn to_owned_item(&self) -> OwnedDiffItem<T> {
+        match *self {
+            DiffItem::Add { start_from, start_to, end_to, items } => {
+                OwnedDiffItem::Add {
+                    start_from,
+                    start_to,
+                    end_to,
+                    items: items.to_vec(),
+                }
+            }
+            DiffItem::Delete { start_from, end_from, start_to, items } => {
+                OwnedDiffItem::Delete {
+                    start_from,
+                    end_from,
+                    start_to,
+                    items: items.to_vec(),
+                }
+            }
+            DiffItem::Change { start_from, end_from, start_to, end_to, from, to } => {
+                OwnedDiffItem::Change {
+                    start_from,
+                    end_from,
+                    start_to,
+                    end_to,
+                    from: from.to_vec(),
+                    to: to.to_vec(),
+                }
+            }
+        }
+    }
+}
+
+impl<'a, 'i, T: Clone> From<&'i DiffItem<'a, T>> for OwnedDiffItem<T> {
+    fn from(item: &'i DiffItem<'a, T>) -> OwnedDiffItem<T> {
+        item.to_owned_item()
+    }
+}
+
+/// A DiffItem that owns its items instead of borrowing them from the
+/// slices that were diffed, so it can be stored, sent to another thread or
+/// (with the "serde" feature) saved and loaded again. The fields mean the
+/// same as in DiffItem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OwnedDiffItem<T> {
+    /// Represents an insertion edit
+    Add {
+        /// The index at which to insert the new items in the "from" slice
+        start_from: usize,
+        /// The starting index of the new items in the "to" slice
+        start_to: usize,
+        /// The ending index of the new items in the "to" slice
+        end_to: usize,
+        /// The items to be inserted
+        items: Vec<T>,
+    },
+    /// Represents a deletion edit
+    Delete {
+        /// The starting index of the items to delete from the "from" slice
+        start_from: usize,
+        /// The ending index of the items to delete from the "from" slice
+        end_from: usize,
+        /// The index in "to" that corresponds to "start_from"
+        start_to: usize,
+        /// The items to be deleted
+        items: Vec<T>,
+    },
+    /// Represents a change edit
+    Change {
+        /// The starting index of the items to Change from the "from" slice
+        start_from: usize,
+        /// The ending index of the items to Change from the "from" slice
+        end_from: usize,
+        /// The starting index of the items to Change from the "to" slice
+        start_to: usize,
+        /// The ending index of the items to Change from the "to" slice
+        end_to: usize,
+        /// The lines that will be Changed in "from"
+        from: Vec<T>,
+        /// The change lines from "to"
+        to: Vec<T>,
+    },
+}
+
+impl<T> OwnedDiffItem<T> {
+    /// A DiffItem borrowing this edit's items, for use with patch,
+    /// patch_all and the functions that print a diff
+    pub fn as_diff_item(&self) -> DiffItem<'_, T> {
+        match *self {
+            OwnedDiffItem::Add { start_from, start_to, end_to, ref items } => {
+                DiffItem::Add {
+                    start_from,
+                    start_to,
+                    end_to,
+                    items,
+                }
+            }
+            OwnedDiffItem::Delete { start_from, end_from, start_to, ref items } => {
+                DiffItem::Delete {
+                    start_from,
+                    end_from,
+                    start_to,
+                    items,
+                }
+            }
+            OwnedDiffItem::Change { start_from, end_from, start_to, end_to, ref from, ref to } => {
+                DiffItem::Change {
+                    start_from,
+                    end_from,
+                    start_to,
+                    end_to,
+                    from,
+                    to,
+                }
+            }
+        }
+    }
+}
+
+impl<T: Display> Display for OwnedDiffItem<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        self.as_diff_item().fmt(f)
+    }
+}
+
 impl<'a, T: 'a> Display for DiffItem<'a, T>
-    where T: PartialEq + Debug + Display
+    where T: Display
 {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         match *self {
@@ -58,7 +295,7 @@ impl<'a, T: 'a> Display for DiffItem<'a, T>
                 items,
             } => {
                 if items.len() > 1 {
-                    writeln!(f, "{}a{},{}", start_from, start_to, end_to).unwrap();
+                    writeln!(f, "{}a{},{}", start_from, start_to, end_to - 1).unwrap();
                 } else {
                     writeln!(f, "{}a{}", start_from, start_to).unwrap();
 
@@ -95,14 +332,19 @@ impl<'a, T: 'a> Display for DiffItem<'a, T>
                 to,
             } => {
                 if from.len() > 1 {
-                    writeln!(f, "{},{}c{},{}", start_from, end_from, start_to, end_to).unwrap();
+                    write!(f, "{},{}c", start_from, end_from).unwrap();
+                } else {
+                    write!(f, "{}c", start_from).unwrap();
+                }
+                if to.len() > 1 {
+                    writeln!(f, "{},{}", start_to, end_to).unwrap();
                 } else {
-                    writeln!(f, "{}c{}", start_from, start_to).unwrap();
+                    writeln!(f, "{}", start_to).unwrap();
                 }
                 for item in from {
                     writeln!(f, "< {}", item).unwrap();
                 }
-                writeln!(f, "-----------").unwrap();
+                writeln!(f, "---").unwrap();
                 for item in to {
                     writeln!(f, "> {}", item).unwrap();
                 }
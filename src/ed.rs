@@ -0,0 +1,108 @@
+use diffitem::DiffItem;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Writes an edit script as commands for the ed editor (diff -e).
+///
+/// The edits are written last to first, so the line numbers of each command
+/// still refer to the original "from" when it runs. Inserted lines are ended
+/// by a "." line; a line that is just "." is written as ".." and fixed up
+/// with an s command, since ed would otherwise stop inserting there.
+///
+/// # Example
+/// ```
+/// use diff::{diff, write_ed_script};
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+///
+/// let mut output = Vec::new();
+/// write_ed_script(&mut output, &diff(&from, &to)).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "3c\nanother\n.\n");
+/// ```
+pub fn write_ed_script<'a, T, W>(mut writer: W, diffs: &[DiffItem<'a, T>]) -> io::Result<()>
+    where T: Display,
+          W: Write
+{
+    for item in diffs.iter().rev() {
+        let command = match *item {
+            DiffItem::Add { start_from, .. } => format!("{}a", start_from),
+            DiffItem::Delete { .. } => format!("{}d", ed_range(&item.from_range())),
+            DiffItem::Change { .. } => format!("{}c", ed_range(&item.from_range())),
+        };
+        writeln!(writer, "{}", command)?;
+
+        let inserted = item.inserted();
+        if inserted.is_empty() {
+            continue;
+        }
+        let mut inserting = true;
+        for line in inserted {
+            if !inserting {
+                writeln!(writer, "a")?;
+                inserting = true;
+            }
+            let line = line.to_string();
+            if line == "." {
+                // stop inserting after "..", then strip the extra dot
+                writeln!(writer, "..\n.\ns/.//")?;
+                inserting = false;
+            } else {
+                writeln!(writer, "{}", line)?;
+            }
+        }
+        if inserting {
+            writeln!(writer, ".")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes an edit script in RCS format (diff -n).
+///
+/// The edits are written first to last. "dL N" deletes N lines starting at
+/// line L, and "aL N" adds the N lines that follow it after line L, both
+/// numbered as in the original "from". A Change is a d followed by an a.
+///
+/// # Example
+/// ```
+/// use diff::{diff, write_rcs};
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+///
+/// let mut output = Vec::new();
+/// write_rcs(&mut output, &diff(&from, &to)).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "d3 1\na3 1\nanother\n");
+/// ```
+pub fn write_rcs<'a, T, W>(mut writer: W, diffs: &[DiffItem<'a, T>]) -> io::Result<()>
+    where T: Display,
+          W: Write
+{
+    for item in diffs {
+        let removed = item.from_range();
+        if removed.start < removed.end {
+            writeln!(writer, "d{} {}", removed.start + 1, removed.end - removed.start)?;
+        }
+
+        let inserted = item.inserted();
+        if !inserted.is_empty() {
+            writeln!(writer, "a{} {}", removed.end, inserted.len())?;
+            for line in inserted {
+                writeln!(writer, "{}", line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Formats a non-empty 0-based range of lines as "first,last", or just the
+/// line number when there is only one
+fn ed_range(range: &Range<usize>) -> String {
+    if range.end == range.start + 1 {
+        format!("{}", range.end)
+    } else {
+        format!("{},{}", range.start + 1, range.end)
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::{Display, Formatter, Error};
+use std::fs;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The name and modification time of one side of a diff, as shown in the
+/// header of unified and context diffs.
+///
+/// Times are always shown in UTC. GNU diff shows them in the local time
+/// zone instead; a unified diff header says which with its "+0000", so it
+/// names the same instant either way, but the context diff format has no
+/// room for a time zone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHeader {
+    /// The path shown for the file
+    pub path: String,
+    /// When the file was last modified, if known
+    pub modified: Option<SystemTime>,
+}
+
+impl FileHeader {
+    /// A header showing only a path
+    pub fn new(path: &str) -> FileHeader {
+        FileHeader {
+            path: path.to_string(),
+            modified: None,
+        }
+    }
+
+    /// A header for a file on disk, with its modification time
+    pub fn for_file(path: &str) -> io::Result<FileHeader> {
+        let modified = fs::metadata(path)?.modified()?;
+        Ok(FileHeader {
+            path: path.to_string(),
+            modified: Some(modified),
+        })
+    }
+}
+
+impl Display for FileHeader {
+    /// Formats as the path, followed by a tab and the modification time in
+    /// UTC, in the format GNU diff uses for unified diffs with the offset
+    /// always +0000, e.g. "a.txt\t2017-03-09 18:30:00.000000000 +0000"
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.path)?;
+        if let Some(modified) = self.modified {
+            let time = DateTime::utc(modified);
+            write!(f,
+                   "\t{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:09} +0000",
+                   time.year,
+                   time.month,
+                   time.day,
+                   time.hour,
+                   time.minute,
+                   time.second,
+                   time.nanos)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a header the way GNU diff does for context diffs, e.g.
+/// "a.txt\tThu Mar  9 18:30:00 2017". The time is in UTC, though the
+/// format doesn't say so.
+pub fn context_header(header: &FileHeader) -> String {
+    match header.modified {
+        Some(modified) => {
+            let time = DateTime::utc(modified);
+            format!("{}\t{} {} {:2} {:02}:{:02}:{:02} {}",
+                    header.path,
+                    WEEKDAYS[time.weekday as usize],
+                    MONTHS[time.month as usize - 1],
+                    time.day,
+                    time.hour,
+                    time.minute,
+                    time.second,
+                    time.year)
+        }
+        None => header.path.clone(),
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct",
+                            "Nov", "Dec"];
+
+/// A point in time broken down into calendar fields, in UTC
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    /// Days since Sunday
+    weekday: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    nanos: u32,
+}
+
+impl DateTime {
+    fn utc(time: SystemTime) -> DateTime {
+        let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            Err(before) => {
+                let before = before.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    n => (-(before.as_secs() as i64) - 1, 1_000_000_000 - n),
+                }
+            }
+        };
+        let days = secs.div_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        let time = secs.rem_euclid(86_400);
+        DateTime {
+            year,
+            month,
+            day,
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7),
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+            nanos,
+        }
+    }
+}
+
+/// Converts a number of days since 1970-01-01 to a (year, month, day) date
+/// in the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 -
+                       day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // months counted from March, so the leap day comes last
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
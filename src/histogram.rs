@@ -0,0 +1,131 @@
+use diffhelpers::EditFlags;
+use myers::linear_myers_diffs;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+/// Items occurring more often than this in "from" are never used to split a region
+const MAX_CHAIN_LENGTH: usize = 64;
+
+/// A piece of work for histogram_diffs, handled in stack order
+enum Task {
+    /// Diff from[from_range] against to[to_range]
+    Region(Range<usize>, Range<usize>),
+    /// A run of items unchanged in both sequences
+    Same(usize),
+}
+
+/// A common run of items: from[from_start..from_start + len] == to[to_start..to_start + len]
+struct Split {
+    from_start: usize,
+    to_start: usize,
+    len: usize,
+}
+
+/// Find all the edits necessary to make "from" match "to" using histogram diff,
+/// the algorithm behind git's --histogram.
+///
+/// A histogram of the items in "from" is used to find the common run of items
+/// containing the rarest item, which is kept unchanged; the regions before and
+/// after it are diffed the same way. This extends patience diff to inputs with
+/// few unique items. Regions with nothing in common fall back to Myers' algorithm.
+/// Produces the same kind of Vec<EditFlags> as make_diffs.
+pub fn histogram_diffs<T: Eq + Hash>(from: &[T], to: &[T]) -> Vec<EditFlags> {
+    let mut diffs = Vec::with_capacity(from.len() + to.len());
+    let mut tasks = vec![Task::Region(0..from.len(), 0..to.len())];
+
+    while let Some(task) = tasks.pop() {
+        let (from_range, to_range) = match task {
+            Task::Same(len) => {
+                for _ in 0..len {
+                    diffs.push(EditFlags::Same);
+                }
+                continue;
+            }
+            Task::Region(from_range, to_range) => (from_range, to_range),
+        };
+
+        if from_range.start == from_range.end || to_range.start == to_range.end {
+            diffs.extend(linear_myers_diffs(&from[from_range], &to[to_range]));
+            continue;
+        }
+
+        match find_split(from, from_range.clone(), to, to_range.clone()) {
+            Some(split) => {
+                // pushed in reverse so they are handled in order
+                tasks.push(Task::Region(split.from_start + split.len..from_range.end,
+                                        split.to_start + split.len..to_range.end));
+                tasks.push(Task::Same(split.len));
+                tasks.push(Task::Region(from_range.start..split.from_start,
+                                        to_range.start..split.to_start));
+            }
+            None => diffs.extend(linear_myers_diffs(&from[from_range], &to[to_range])),
+        }
+    }
+
+    diffs
+}
+
+/// Helper function for histogram_diffs
+/// Returns the longest common run of items containing the item with the
+/// fewest occurrences in from[from_range], or None if every shared item
+/// occurs more than MAX_CHAIN_LENGTH times.
+fn find_split<T: Eq + Hash>(from: &[T],
+                            from_range: Range<usize>,
+                            to: &[T],
+                            to_range: Range<usize>)
+                            -> Option<Split> {
+    // item -> every index at which it occurs in from[from_range]
+    let mut histogram: HashMap<&T, Vec<usize>> = HashMap::new();
+    for i in from_range.clone() {
+        histogram.entry(&from[i]).or_default().push(i);
+    }
+
+    let mut best: Option<Split> = None;
+    let mut best_count = MAX_CHAIN_LENGTH + 1;
+
+    let mut j = to_range.start;
+    while j < to_range.end {
+        let mut next_j = j + 1;
+        let occurrences = match histogram.get(&to[j]) {
+            Some(occurrences) if occurrences.len() <= best_count => occurrences,
+            _ => {
+                j = next_j;
+                continue;
+            }
+        };
+
+        for &i in occurrences {
+            // the rarest item in the run decides how good a split it makes
+            let mut count = occurrences.len();
+            let (mut from_start, mut to_start) = (i, j);
+            while from_start > from_range.start && to_start > to_range.start &&
+                  from[from_start - 1] == to[to_start - 1] {
+                from_start -= 1;
+                to_start -= 1;
+                count = count.min(histogram[&from[from_start]].len());
+            }
+            let (mut from_end, mut to_end) = (i + 1, j + 1);
+            while from_end < from_range.end && to_end < to_range.end &&
+                  from[from_end] == to[to_end] {
+                count = count.min(histogram[&from[from_end]].len());
+                from_end += 1;
+                to_end += 1;
+            }
+
+            let len = from_end - from_start;
+            if best.as_ref().is_none_or(|best| best.len < len) || count < best_count {
+                best = Some(Split {
+                    from_start,
+                    to_start,
+                    len,
+                });
+                best_count = count;
+            }
+            next_j = next_j.max(to_end);
+        }
+        j = next_j;
+    }
+
+    best
+}
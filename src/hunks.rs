@@ -0,0 +1,98 @@
+use diffitem::DiffItem;
+use std::ops::Range;
+
+/// A group of edits that are close enough together to be shown with
+/// shared context lines, as in unified and context diffs
+pub struct Hunk<'d, 'a: 'd, T: 'a> {
+    /// The edits in this hunk
+    pub items: &'d [DiffItem<'a, T>],
+    /// The part of "from" shown, including context (0-based)
+    pub from: Range<usize>,
+    /// The part of "to" shown, including context (0-based)
+    pub to: Range<usize>,
+}
+
+/// An edit script that can be grouped into hunks: a slice or Vec of
+/// DiffItems, or the LineDiff that diff_lines returns
+pub trait EditScript<'a, T: 'a> {
+    /// The edits, in order
+    fn items(&self) -> &[DiffItem<'a, T>];
+
+    /// Whether the edit at "index" only changes lines that ignore_blank_lines
+    /// or ignore_matching_lines let diff_lines leave out. Such an edit only
+    /// joins a hunk when it is within the context of the edit before it.
+    fn is_ignorable(&self, _index: usize) -> bool {
+        false
+    }
+}
+
+impl<'a, T: 'a> EditScript<'a, T> for [DiffItem<'a, T>] {
+    fn items(&self) -> &[DiffItem<'a, T>] {
+        self
+    }
+}
+
+impl<'a, T: 'a> EditScript<'a, T> for Vec<DiffItem<'a, T>> {
+    fn items(&self) -> &[DiffItem<'a, T>] {
+        self
+    }
+}
+
+/// Groups an edit script into hunks with up to "context" unchanged lines
+/// around every edit. Edits separated by no more than twice that many
+/// unchanged lines end up in the same hunk, as do ignorable edits within
+/// the context of the edit before them.
+pub fn hunks<'d, 'a, T, S>(diffs: &'d S,
+                           from_len: usize,
+                           to_len: usize,
+                           context: usize)
+                           -> Vec<Hunk<'d, 'a, T>>
+    where S: EditScript<'a, T> + ?Sized
+{
+    let items = diffs.items();
+    group(items, |index| diffs.is_ignorable(index), context)
+        .into_iter()
+        .map(|range| {
+            let (first, last) = (&items[range.start], &items[range.end - 1]);
+            let (first_from, first_to) = (first.from_range(), first.to_range());
+            let (last_from, last_to) = (last.from_range(), last.to_range());
+            // the context is unchanged, so it is as long on both sides
+            let before = context.min(first_from.start).min(first_to.start);
+            let after = context.min(from_len - last_from.end).min(to_len - last_to.end);
+
+            Hunk {
+                items: &items[range],
+                from: first_from.start - before..last_from.end + after,
+                to: first_to.start - before..last_to.end + after,
+            }
+        })
+        .collect()
+}
+
+/// Splits edits into the index ranges of their hunks the way GNU diff does:
+/// an edit joins the hunk before it if fewer than 2 * context + 1 unchanged
+/// lines come between them, or fewer than "context" for an ignorable edit.
+/// diff_lines decides which edits to leave out with the same rule, so the
+/// edits it keeps fall into the same hunks again.
+pub fn group<'a, T, F>(items: &[DiffItem<'a, T>], ignorable: F, context: usize) -> Vec<Range<usize>>
+    where F: Fn(usize) -> bool
+{
+    let mut result = vec![];
+    let mut first = 0;
+
+    while first < items.len() {
+        let mut last = first;
+        while last + 1 < items.len() {
+            let gap = items[last + 1].from_range().start - items[last].from_range().end;
+            let threshold = if ignorable(last + 1) { context } else { 2 * context + 1 };
+            if gap >= threshold {
+                break;
+            }
+            last += 1;
+        }
+        result.push(first..last + 1);
+        first = last + 1;
+    }
+
+    result
+}
@@ -0,0 +1,123 @@
+use diffitem::DiffItem;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Writes an edit script as a single line of JSON, followed by a newline.
+///
+/// The schema is stable; fields may be added in later versions, but none
+/// will be removed or change meaning:
+///
+/// ```text
+/// {
+///   "files": {"from": <string>, "to": <string>},
+///   "summary": {
+///     "changes": <number of edits>,
+///     "added": <number of lines only in "to">,
+///     "deleted": <number of lines only in "from">
+///   },
+///   "changes": [
+///     {
+///       "kind": "add" | "delete" | "change",
+///       "from": {"start": <number>, "end": <number>, "lines": [<string>, ...]},
+///       "to": {"start": <number>, "end": <number>, "lines": [<string>, ...]}
+///     },
+///     ...
+///   ]
+/// }
+/// ```
+///
+/// Ranges are 1-based and inclusive, counting lines of each file. A side
+/// with no lines (the "from" of an add, the "to" of a delete) has end equal
+/// to start - 1, where start is the line that follows the position the
+/// lines were added at or deleted from. Lines are written with Display.
+///
+/// # Example
+/// ```
+/// use diff::{diff, write_json};
+///
+/// let from = vec!["a", "b"];
+/// let to = vec!["a", "c", "d"];
+///
+/// let mut output = Vec::new();
+/// write_json(&mut output, "old.txt", "new.txt", &diff(&from, &to)).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "{\"files\":{\"from\":\"old.txt\",\"to\":\"new.txt\"},\
+///             \"summary\":{\"changes\":1,\"added\":2,\"deleted\":1},\
+///             \"changes\":[{\"kind\":\"change\",\
+///             \"from\":{\"start\":2,\"end\":2,\"lines\":[\"b\"]},\
+///             \"to\":{\"start\":2,\"end\":3,\"lines\":[\"c\",\"d\"]}}]}\n");
+/// ```
+pub fn write_json<'a, T, W>(mut writer: W,
+                            from_path: &str,
+                            to_path: &str,
+                            diffs: &[DiffItem<'a, T>])
+                            -> io::Result<()>
+    where T: Display,
+          W: Write
+{
+    let added: usize = diffs.iter().map(|item| item.inserted().len()).sum();
+    let deleted: usize = diffs.iter().map(|item| item.removed().len()).sum();
+    write!(writer,
+           "{{\"files\":{{\"from\":{},\"to\":{}}},",
+           json_string(from_path),
+           json_string(to_path))?;
+    write!(writer,
+           "\"summary\":{{\"changes\":{},\"added\":{},\"deleted\":{}}},",
+           diffs.len(),
+           added,
+           deleted)?;
+
+    write!(writer, "\"changes\":[")?;
+    for (n, item) in diffs.iter().enumerate() {
+        if n > 0 {
+            write!(writer, ",")?;
+        }
+        let kind = match *item {
+            DiffItem::Add { .. } => "add",
+            DiffItem::Delete { .. } => "delete",
+            DiffItem::Change { .. } => "change",
+        };
+        write!(writer, "{{\"kind\":\"{}\",\"from\":", kind)?;
+        write_side(&mut writer, item.from_range(), item.removed())?;
+        write!(writer, ",\"to\":")?;
+        write_side(&mut writer, item.to_range(), item.inserted())?;
+        write!(writer, "}}")?;
+    }
+    writeln!(writer, "]}}")
+}
+
+/// Writes one side of an edit: its 1-based inclusive range and its lines
+fn write_side<T: Display, W: Write>(writer: &mut W, range: Range<usize>, lines: &[T])
+                                    -> io::Result<()> {
+    write!(writer,
+           "{{\"start\":{},\"end\":{},\"lines\":[",
+           range.start + 1,
+           range.end)?;
+    for (n, line) in lines.iter().enumerate() {
+        if n > 0 {
+            write!(writer, ",")?;
+        }
+        write!(writer, "{}", json_string(&line.to_string()))?;
+    }
+    write!(writer, "]}}")
+}
+
+/// Quotes text as a JSON string
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -1,59 +1,119 @@
-/// libdiff.rs
-///
-/// A library for calculating the diff of two sequences
-///
-/// Use diff(&from, &to) to generate a list of changes to make "from"
-/// match "to".
-/// This edit script will be represented by a Vec<DiffItem>
-/// Where a DiffItem can be either an Add, Delete, or Change, and will containi
-/// the indices in each sequence where the edit occurs and the content of the edit.
-///
-/// Once an edit script has been generated, printing each item will produce output
-/// matching the unix diff utility.
-///
-/// A patch function is also provided to apply the change specified by a single
-/// DiffItem to a sequence.
-///
-/// # Example
-/// ```
-/// use diff::{diff, patch, pretty_print};
-/// use std::io::stdout;
-///
-/// let from = vec!["this", "is", "an", "example"];
-/// let to = vec!["this", "is", "another", "example"];
-///
-/// // generate the edit script
-/// let changes = diff(&from, &to);
-///
-/// // print the diff
-/// for edit in &changes {
-///    print!("{}", *edit);
-/// }
-///
-/// // apply the first edit in the script
-/// let patched = patch(&from, &changes[0]);
-///
-/// // print out a prettified representation of a single change
-/// pretty_print(stdout(), &from, &changes[0]);
-/// ```
+//! libdiff.rs
+//!
+//! A library for calculating the diff of two sequences
+//!
+//! Use diff(&from, &to) to generate a list of changes to make "from"
+//! match "to".
+//! This edit script will be represented by a Vec<DiffItem>
+//! Where a DiffItem can be either an Add, Delete, or Change, and will containi
+//! the indices in each sequence where the edit occurs and the content of the edit.
+//!
+//! Once an edit script has been generated, printing each item will produce output
+//! matching the unix diff utility.
+//!
+//! A patch function is also provided to apply the change specified by a single
+//! DiffItem to a sequence, and patch_all applies a whole edit script.
+//! unpatch_all applies one in reverse, turning "to" back into "from".
+//!
+//! # Example
+//! ```
+//! use diff::{diff, patch, pretty_print};
+//! use std::io::stdout;
+//!
+//! let from = vec!["this", "is", "an", "example"];
+//! let to = vec!["this", "is", "another", "example"];
+//!
+//! // generate the edit script
+//! let changes = diff(&from, &to);
+//!
+//! // print the diff
+//! for edit in &changes {
+//!    print!("{}", *edit);
+//! }
+//!
+//! // apply the first edit in the script
+//! let patched = patch(&from, &changes[0]);
+//!
+//! // print out a prettified representation of a single change
+//! pretty_print(stdout(), &from, &changes[0]);
+//! ```
 
 extern crate colored;
+#[cfg(feature = "serde")]
+extern crate serde;
+extern crate regex;
+extern crate unicode_segmentation;
 use colored::*;
-use std::fmt::{Display, Debug};
+use std::fmt::Display;
+use std::hash::Hash;
 use std::io::Write;
 
 mod diffhelpers;
 use diffhelpers::*;
+pub use diffhelpers::EditFlags;
 
 mod longest_common_subseq;
-use longest_common_subseq::build_lcs_table;
+mod myers;
+mod patience;
+mod histogram;
+
+mod preprocess;
+use preprocess::{trimmed_diffs, intern};
 
 mod diffitem;
-pub use diffitem::DiffItem;
+pub use diffitem::{DiffItem, OwnedDiffItem};
+
+mod algorithm;
+pub use algorithm::{Algorithm, DiffAlgorithm, Lcs, Myers, LinearMyers, Patience, Histogram};
+
+mod options;
+pub use options::{diff_lines, diff_lines_marked, DiffOptions, LineDiff, MissingNewline,
+                  Whitespace};
+
+mod hunks;
+pub use hunks::EditScript;
+
+mod header;
+pub use header::FileHeader;
+
+mod unified;
+pub use unified::{write_unified, write_unified_file, write_unified_header};
+
+mod context;
+pub use context::{write_context, write_context_file, write_context_header};
+
+mod ed;
+pub use ed::{write_ed_script, write_rcs};
+
+mod json;
+pub use json::write_json;
+
+mod side_by_side;
+pub use side_by_side::{write_side_by_side, write_side_by_side_marked, SideBySideOptions};
+
+mod apply;
+pub use apply::{patch_all, unpatch_all, apply_hunks, AppliedHunks, HunkOutcome, PatchError};
+
+mod parse;
+pub use parse::{parse_unified, parse_normal, FilePatch, PatchHunk, PatchLine, ParseError};
+
+mod merge;
+pub use merge::{merge, write_merge, MergeChunk, MergeLabels};
+
+mod word_diff;
+pub use word_diff::{diff_words, write_word_diff, WordStyle};
+
+mod refine;
+pub use refine::{diff_chars, refine_line, MIN_SIMILARITY};
+use refine::write_refined;
 
 /// Calculate a diff. Takes in two slices and returns a Vec<DiffItem>
 /// containing the changes necessary to make "from" look like "to"
 ///
+/// Small inputs are diffed with a full LCS table. Larger ones have their
+/// common prefix and suffix stripped, and the rest is diffed with the linear
+/// space variant of Myers' algorithm (see Algorithm::for_lengths).
+///
 /// # Example
 /// ```
 /// use diff::diff;
@@ -62,17 +122,152 @@ pub use diffitem::DiffItem;
 /// let to = vec!["this", "is", "another", "example"];
 /// let diffs = diff(&from, &to);
 /// ```
-pub fn diff<'a, T>(from: &'a [T], to: &'a [T]) -> Vec<DiffItem<'a, T>>
-    where T: PartialEq + Display + Debug
+pub fn diff<'a, T: PartialEq>(from: &'a [T], to: &'a [T]) -> Vec<DiffItem<'a, T>> {
+    let diffs = if Algorithm::for_lengths(from.len(), to.len()) == Algorithm::Lcs {
+        Lcs.diffs(from, to)
+    } else {
+        trimmed_diffs(from, to, |from, to| LinearMyers.diffs(from, to))
+    };
+    convert_to_diffitems(from, to, &diffs)
+}
+
+/// Calculate a diff using a specific algorithm.
+///
+/// The items are replaced by integer IDs before running the algorithm, so
+/// long items (like lines of text) are only hashed once and never compared
+/// again. Except with Algorithm::Lcs, the common prefix and suffix of the
+/// slices are stripped first as well.
+///
+/// # Example
+/// ```
+/// use diff::{diff_with, Algorithm};
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+/// let diffs = diff_with(&from, &to, Algorithm::Patience);
+/// ```
+pub fn diff_with<'a, T>(from: &'a [T], to: &'a [T], algorithm: Algorithm) -> Vec<DiffItem<'a, T>>
+    where T: Eq + Hash
 {
-    let table = build_lcs_table(from, to);
-    let mut diffs: Vec<_> = vec![];
-    make_diffs(&table, from, to, from.len(), to.len(), &mut diffs);
+    convert_to_diffitems(from, to, &key_diffs(from, to, Some(algorithm)))
+}
+
+/// Calculate a diff using any implementation of DiffAlgorithm.
+///
+/// Panics if the edits returned by the algorithm don't account for every
+/// item of both slices.
+///
+/// # Example
+/// ```
+/// use diff::{diff_using, Myers};
+///
+/// let from = vec![0.5, 1.0, 1.5];
+/// let to = vec![0.5, 2.0, 1.5];
+/// let diffs = diff_using(&from, &to, &Myers);
+/// ```
+pub fn diff_using<'a, T, A>(from: &'a [T], to: &'a [T], algorithm: &A) -> Vec<DiffItem<'a, T>>
+    where A: DiffAlgorithm<T> + ?Sized
+{
+    let diffs = algorithm.diffs(from, to);
+    let same = diffs.iter().filter(|&d| *d == EditFlags::Same).count();
+    let deleted = diffs.iter().filter(|&d| *d == EditFlags::Delete).count();
+    assert!(same + deleted == from.len() && diffs.len() - deleted == to.len(),
+            "DiffAlgorithm returned edits for {} and {} items, expected {} and {}",
+            same + deleted,
+            diffs.len() - deleted,
+            from.len(),
+            to.len());
     convert_to_diffitems(from, to, &diffs)
 }
 
+/// Calculate a diff, deciding whether two items match with a closure
+/// instead of PartialEq. The DiffItems still refer to the original items.
+///
+/// Since nothing is known about the closure beyond matching pairs of items,
+/// this always uses the linear space Myers algorithm, after stripping the
+/// common prefix and suffix. The closure doesn't need to be an equivalence
+/// relation (a tolerance for floats isn't transitive), but the result is
+/// only a shortest edit script if it is.
+///
+/// # Example
+/// ```
+/// use diff::diff_by;
+///
+/// let from: Vec<f64> = vec![1.0, 2.0, 3.0];
+/// let to = vec![1.0001, 2.5, 2.9999];
+/// let changes = diff_by(&from, &to, |a, b| (a - b).abs() < 0.01);
+/// assert_eq!(changes.len(), 1);
+/// assert_eq!(changes[0].removed(), &[2.0]);
+/// ```
+pub fn diff_by<'a, T, F>(from: &'a [T], to: &'a [T], eq: F) -> Vec<DiffItem<'a, T>>
+    where F: Fn(&T, &T) -> bool
+{
+    let wrap = |items: &'a [T]| -> Vec<By<T, F>> {
+        items.iter().map(|item| By { item, eq: &eq }).collect()
+    };
+    let (from_by, to_by) = (wrap(from), wrap(to));
+    let diffs = trimmed_diffs(&from_by, &to_by, |from, to| LinearMyers.diffs(from, to));
+    convert_to_diffitems(from, to, &diffs)
+}
+
+/// An item compared with the closure given to diff_by
+struct By<'a, T: 'a, F: 'a> {
+    item: &'a T,
+    eq: &'a F,
+}
+
+impl<'a, T, F> PartialEq for By<'a, T, F>
+    where F: Fn(&T, &T) -> bool
+{
+    fn eq(&self, other: &By<'a, T, F>) -> bool {
+        (self.eq)(self.item, other.item)
+    }
+}
+
+/// Calculate a diff, comparing a key computed from each item instead of the
+/// items themselves. The key is computed once per item, and the diff is
+/// calculated as diff would for the keys; the DiffItems still refer to the
+/// original items.
+///
+/// # Example
+/// ```
+/// use diff::diff_by_key;
+///
+/// let from = vec!["Hello", "World", "foo"];
+/// let to = vec!["hello", "WORLD", "bar"];
+/// let changes = diff_by_key(&from, &to, |word| word.to_lowercase());
+/// assert_eq!(changes.len(), 1);
+/// assert_eq!(changes[0].inserted(), &["bar"]);
+/// ```
+pub fn diff_by_key<'a, T, K, F>(from: &'a [T], to: &'a [T], key: F) -> Vec<DiffItem<'a, T>>
+    where K: Eq + Hash,
+          F: FnMut(&T) -> K
+{
+    let mut key = key;
+    let from_keys: Vec<K> = from.iter().map(&mut key).collect();
+    let to_keys: Vec<K> = to.iter().map(&mut key).collect();
+    convert_to_diffitems(from, to, &key_diffs(&from_keys, &to_keys, None))
+}
+
+/// The edits for two slices of keys, using the given algorithm or the one
+/// diff would choose. The keys are interned first, as in diff_with.
+fn key_diffs<K: Eq + Hash>(from: &[K], to: &[K], algorithm: Option<Algorithm>) -> Vec<EditFlags> {
+    let (from_ids, to_ids) = intern(from, to);
+    let algorithm = algorithm.unwrap_or_else(|| Algorithm::for_lengths(from.len(), to.len()));
+    // the LCS table needs to see the whole input, as in diff_with
+    if algorithm == Algorithm::Lcs {
+        algorithm.diffs(&from_ids, &to_ids)
+    } else {
+        trimmed_diffs(&from_ids, &to_ids, |from, to| algorithm.diffs(from, to))
+    }
+}
+
 /// Applies an edit represented by a DiffItem to a slice.
 ///
+/// The line numbers of a DiffItem refer to the original "from", so applying
+/// a second edit to the result goes wrong. Use patch_all to apply a whole
+/// edit script.
+///
 /// # Example
 /// ```
 /// use diff::{diff, patch};
@@ -85,9 +280,7 @@ pub fn diff<'a, T>(from: &'a [T], to: &'a [T]) -> Vec<DiffItem<'a, T>>
 /// // Apply the first edit:
 /// let patched = patch(&from, &changes[0]);
 /// ```
-pub fn patch<'a, T>(input: &[T], diff: &DiffItem<'a, T>) -> Vec<T>
-    where T: Clone + Debug + PartialEq + Display
-{
+pub fn patch<'a, T: Clone>(input: &[T], diff: &DiffItem<'a, T>) -> Vec<T> {
     let mut changes: Vec<T>;
 
     match *diff {
@@ -101,18 +294,14 @@ pub fn patch<'a, T>(input: &[T], diff: &DiffItem<'a, T>) -> Vec<T>
             for i in to {
                 changes.push(i.clone());
             }
-            for j in end_from..input.len() {
-                changes.push(input[j].clone());
-            }
+            changes.extend_from_slice(&input[end_from..]);
         }
         DiffItem::Add { start_from, items, .. } => {
             changes = input[0..start_from].to_vec();
             for i in items {
                 changes.push(i.clone());
             }
-            for j in start_from..input.len() {
-                changes.push(input[j].clone());
-            }
+            changes.extend_from_slice(&input[start_from..]);
         }
         DiffItem::Delete {
             start_from,
@@ -120,9 +309,7 @@ pub fn patch<'a, T>(input: &[T], diff: &DiffItem<'a, T>) -> Vec<T>
             ..
         } => {
             changes = input[0..start_from - 1].to_vec();
-            for i in end_from..input.len() {
-                changes.push(input[i].clone());
-            }
+            changes.extend_from_slice(&input[end_from..]);
         }
     }
 
@@ -131,6 +318,10 @@ pub fn patch<'a, T>(input: &[T], diff: &DiffItem<'a, T>) -> Vec<T>
 
 /// Prints a colored representation of how to apply an edit to a sequence
 ///
+/// For a Change, the old and new lines are paired up in order, and where a
+/// pair is similar enough (see refine_line) only the characters that differ
+/// are highlighted, in reversed color.
+///
 /// # Example
 /// ```
 /// use diff::{diff, pretty_print};
@@ -144,7 +335,7 @@ pub fn patch<'a, T>(input: &[T], diff: &DiffItem<'a, T>) -> Vec<T>
 /// pretty_print(stdout(), &from, &changes[0]);
 /// ```
 pub fn pretty_print<'a, T, W>(mut writer: W, original: &'a [T], diff: &DiffItem<'a, T>)
-    where T: Clone + Debug + PartialEq + Display,
+    where T: Display,
           W: Write
 {
     //println!("How to make file 1 like file 2:");
@@ -157,30 +348,44 @@ pub fn pretty_print<'a, T, W>(mut writer: W, original: &'a [T], diff: &DiffItem<
             ..
         } => {
 
-            for i in 0..start_from - 1 {
-                writeln!(writer, "{}", original[i]).unwrap();
+            for item in &original[..start_from - 1] {
+                writeln!(writer, "{}", item).unwrap();
             }
-            for j in from {
-                writeln!(writer,
-                         "{} {}",
-                         EditFlags::Delete.to_string().red(),
-                         j.to_string().clone().red())
-                        .unwrap();
+            // lines are paired up in order, and the differences within each
+            // pair that is similar enough are highlighted
+            let from_lines: Vec<String> = from.iter().map(|line| line.to_string()).collect();
+            let to_lines: Vec<String> = to.iter().map(|line| line.to_string()).collect();
+            let refined: Vec<_> = from_lines.iter()
+                .zip(&to_lines)
+                .map(|(from_line, to_line)| refine_line(from_line, to_line))
+                .collect();
+            for (i, line) in from_lines.iter().enumerate() {
+                write!(writer, "{} ", EditFlags::Delete.to_string().red()).unwrap();
+                match refined.get(i).and_then(Option::as_ref) {
+                    Some(pieces) => {
+                        write_refined(&mut writer, pieces, EditFlags::Delete, Color::Red).unwrap()
+                    }
+                    _ => write!(writer, "{}", line.red()).unwrap(),
+                }
+                writeln!(writer).unwrap();
             }
-            for k in to {
-                writeln!(writer,
-                         "{} {}",
-                         EditFlags::Add.to_string().green(),
-                         k.to_string().clone().green())
-                        .unwrap();
+            for (i, line) in to_lines.iter().enumerate() {
+                write!(writer, "{} ", EditFlags::Add.to_string().green()).unwrap();
+                match refined.get(i).and_then(Option::as_ref) {
+                    Some(pieces) => {
+                        write_refined(&mut writer, pieces, EditFlags::Add, Color::Green).unwrap()
+                    }
+                    _ => write!(writer, "{}", line.green()).unwrap(),
+                }
+                writeln!(writer).unwrap();
             }
-            for h in end_from..original.len() {
-                writeln!(writer, "{}", original[h]).unwrap();
+            for item in &original[end_from..] {
+                writeln!(writer, "{}", item).unwrap();
             }
         }
         DiffItem::Add { start_from, items, .. } => {
-            for i in 0..start_from {
-                writeln!(writer, "{}", original[i]).unwrap();
+            for item in &original[..start_from] {
+                writeln!(writer, "{}", item).unwrap();
             }
             for j in items {
                 writeln!(writer,
@@ -189,8 +394,8 @@ pub fn pretty_print<'a, T, W>(mut writer: W, original: &'a [T], diff: &DiffItem<
                          j.to_string().clone().green())
                         .unwrap();
             }
-            for h in start_from..original.len() {
-                writeln!(writer, "{}", original[h]).unwrap();
+            for item in &original[start_from..] {
+                writeln!(writer, "{}", item).unwrap();
             }
         }
         DiffItem::Delete {
@@ -199,8 +404,8 @@ pub fn pretty_print<'a, T, W>(mut writer: W, original: &'a [T], diff: &DiffItem<
             items,
             ..
         } => {
-            for i in 0..start_from - 1 {
-                writeln!(writer, "{}", original[i]).unwrap();
+            for item in &original[..start_from - 1] {
+                writeln!(writer, "{}", item).unwrap();
             }
             for j in items {
                 writeln!(writer,
@@ -209,8 +414,8 @@ pub fn pretty_print<'a, T, W>(mut writer: W, original: &'a [T], diff: &DiffItem<
                          j.to_string().clone().red())
                         .unwrap();
             }
-            for h in end_from..original.len() {
-                writeln!(writer, "{}", original[h]).unwrap();
+            for item in &original[end_from..] {
+                writeln!(writer, "{}", item).unwrap();
             }
         }
     }
@@ -219,13 +424,19 @@ pub fn pretty_print<'a, T, W>(mut writer: W, original: &'a [T], diff: &DiffItem<
 #[cfg(test)]
 mod test {
     use super::*;
+    use longest_common_subseq::build_lcs_table;
+    use myers::{myers_diffs, linear_myers_diffs};
+    use patience::patience_diffs;
+    use histogram::histogram_diffs;
+    use preprocess::{common_prefix_len, common_suffix_len};
+    use std::time::{Duration, UNIX_EPOCH};
 
     #[test]
     fn test_lcs_table() {
         let a = vec![1, 2, 3];
         let b = vec![1, 5, 3];
         let table = build_lcs_table(&a, &b);
-        let expected = vec![vec![0, 0, 0, 0], vec![0, 1, 1, 0], vec![0, 1, 1, 0], vec![0, 0, 0, 0]];
+        let expected = vec![vec![0, 0, 0, 0], vec![0, 1, 1, 1], vec![0, 1, 1, 1], vec![0, 1, 1, 2]];
         assert_eq!(table, expected);
     }
 
@@ -240,6 +451,21 @@ mod test {
         assert_eq!(diffs, expected);
     }
 
+    #[test]
+    fn test_make_diffs_million_items() {
+        let a: Vec<_> = (0..1_000_000).collect();
+        let b = vec![0, 500_000, 7];
+        let table = build_lcs_table(&a, &b);
+        let mut diffs: Vec<_> = vec![];
+        make_diffs(&table, &a, &b, a.len(), b.len(), &mut diffs);
+        let same = diffs.iter().filter(|&d| *d == EditFlags::Same).count();
+        let added = diffs.iter().filter(|&d| *d == EditFlags::Add).count();
+        let deleted = diffs.iter().filter(|&d| *d == EditFlags::Delete).count();
+        assert_eq!(same + deleted, a.len());
+        assert_eq!(same + added, b.len());
+        assert_eq!(diffs[0], EditFlags::Same);
+    }
+
     #[test]
     fn test_convert_to_diffitems_change() {
         let a = vec![1, 2, 3];
@@ -320,6 +546,105 @@ mod test {
         assert_eq!(patched, vec!["1", "2", "3", "4"]);
     }
 
+    #[test]
+    fn test_patch_all() {
+        let a: Vec<char> = "the quick brown fox jumps".chars().collect();
+        let b: Vec<char> = "a quick brown cat jumped".chars().collect();
+        assert_eq!(patch_all(&a, &diff(&a, &b)), Ok(b.clone()));
+        for &algorithm in &[Algorithm::Myers, Algorithm::Patience, Algorithm::Histogram] {
+            assert_eq!(patch_all(&a, &diff_with(&a, &b, algorithm)), Ok(b.clone()));
+        }
+        assert_eq!(patch_all(&a, &[]), Ok(a.clone()));
+        assert_eq!(patch_all(&[], &diff(&[], &b)), Ok(b.clone()));
+    }
+
+    /// A linear congruential generator, so the round trip tests below check
+    /// the same inputs every run
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 = self.0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (self.0 >> 33) % n
+        }
+
+        fn sequence(&mut self, max_len: u64, alphabet: u64) -> Vec<u64> {
+            let len = self.below(max_len + 1);
+            (0..len).map(|_| self.below(alphabet)).collect()
+        }
+    }
+
+    #[test]
+    fn test_patch_round_trip() {
+        let mut random = Lcg(12_345);
+        let algorithms = [Algorithm::Lcs,
+                          Algorithm::Myers,
+                          Algorithm::LinearMyers,
+                          Algorithm::Patience,
+                          Algorithm::Histogram];
+        for _ in 0..500 {
+            let alphabet = random.below(6) + 1;
+            let a = random.sequence(30, alphabet);
+            let b = random.sequence(30, alphabet);
+            for &algorithm in &algorithms {
+                let diffs = diff_with(&a, &b, algorithm);
+                assert_eq!(patch_all(&a, &diffs), Ok(b.clone()), "{:?} {:?}", a, b);
+                assert_eq!(unpatch_all(&b, &diffs), Ok(a.clone()), "{:?} {:?}", a, b);
+
+                let inverted: Vec<_> = diffs.iter().map(DiffItem::invert).collect();
+                assert_eq!(patch_all(&b, &inverted), Ok(a.clone()));
+                let twice: Vec<_> = inverted.iter().map(DiffItem::invert).collect();
+                assert_eq!(twice, diffs);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lcs_shortest_script() {
+        let mut random = Lcg(4_242);
+        for _ in 0..500 {
+            let alphabet = random.below(4) + 1;
+            let a = random.sequence(12, alphabet);
+            let b = random.sequence(12, alphabet);
+            let edits = |diffs: Vec<EditFlags>| {
+                diffs.iter().filter(|&d| *d != EditFlags::Same).count()
+            };
+            assert_eq!(edits(Lcs.diffs(&a, &b)), edits(Myers.diffs(&a, &b)), "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_invert_matches_reverse_diff() {
+        let a = vec!["1", "2", "3", "4", "5"];
+        let b = vec!["0", "1", "3", "x", "5", "6"];
+        let diffs = diff_with(&a, &b, Algorithm::Myers);
+        let inverted: Vec<_> = diffs.iter().map(DiffItem::invert).collect();
+        assert_eq!(inverted, diff_with(&b, &a, Algorithm::Myers));
+    }
+
+    #[test]
+    fn test_patch_all_errors() {
+        let a = vec!["1", "2", "3"];
+        let b = vec!["1", "3", "4"];
+        let diffs = diff(&a, &b);
+
+        assert_eq!(patch_all(&a[..1], &diffs),
+                   Err(PatchError::OutOfBounds {
+                       edit: 0,
+                       needed: 2,
+                       len: 1,
+                   }));
+        assert_eq!(patch_all(&["1", "5", "3"], &diffs),
+                   Err(PatchError::Mismatch { edit: 0, line: 2 }));
+
+        let reversed: Vec<_> = diff(&a, &b).into_iter().rev().collect();
+        assert_eq!(patch_all(&a, &reversed), Err(PatchError::OutOfOrder { edit: 1 }));
+        assert_eq!(PatchError::Mismatch { edit: 0, line: 2 }.to_string(),
+                   "edit 1 doesn't match line 2 of the input");
+    }
+
     #[test]
     fn test_diff_longer() {
         let a = "the quick brown fox jumped over the lazy dog";
@@ -329,33 +654,1505 @@ mod test {
         let from = "the";
         let to = "and";
         let add2 = " dog";
-        let diffitems = diff(&a.as_bytes(), &b.as_bytes());
+        let diffitems = diff(a.as_bytes(), b.as_bytes());
         let expected = vec![DiffItem::Delete {
                                 start_from: 4,
                                 end_from: 4,
                                 start_to: 3,
-                                items: &del.as_bytes(),
+                                items: del.as_bytes(),
                             },
                             DiffItem::Add {
                                 start_from: 21,
                                 start_to: 21,
                                 end_to: 22,
-                                items: &add.as_bytes(),
+                                items: add.as_bytes(),
                             },
                             DiffItem::Change {
                                 start_from: 33,
                                 start_to: 33,
                                 end_from: 35,
                                 end_to: 35,
-                                from: &from.as_bytes(),
-                                to: &to.as_bytes(),
+                                from: from.as_bytes(),
+                                to: to.as_bytes(),
                             },
                             DiffItem::Add {
                                 start_from: 40,
                                 start_to: 41,
                                 end_to: 45,
-                                items: &add2.as_bytes(),
+                                items: add2.as_bytes(),
                             }];
         assert_eq!(diffitems, expected);
     }
+
+    #[test]
+    fn test_diff_million_items() {
+        let a: Vec<_> = (0..1_000_000).collect();
+        let b = vec![0, 999_999];
+        let diffitems = diff(&a, &b);
+        assert_eq!(diffitems.len(), 1);
+        match diffitems[0] {
+            DiffItem::Delete { start_from, end_from, start_to, items } => {
+                assert_eq!((start_from, end_from, start_to), (2, 999_999, 1));
+                assert_eq!(items, &a[1..999_999]);
+            }
+            _ => panic!("expected a delete"),
+        }
+
+        let diffitems = diff(&b, &a);
+        assert_eq!(diffitems.len(), 1);
+        assert_eq!(patch(&b, &diffitems[0]), a);
+    }
+
+    #[test]
+    fn test_myers_diffs() {
+        let a = vec![1, 2, 3];
+        let b = vec![1, 5, 3];
+        let diffs = myers_diffs(&a, &b);
+        let expected = vec![EditFlags::Same, EditFlags::Delete, EditFlags::Add, EditFlags::Same];
+        assert_eq!(diffs, expected);
+    }
+
+    #[test]
+    fn test_myers_shortest_script() {
+        // the classic example from Myers' paper has an edit distance of 5
+        let a: Vec<_> = "ABCABBA".chars().collect();
+        let b: Vec<_> = "CBABAC".chars().collect();
+        let diffs = myers_diffs(&a, &b);
+        let edits = diffs.iter().filter(|&d| *d != EditFlags::Same).count();
+        assert_eq!(edits, 5);
+        assert_eq!(diffs.iter().filter(|&d| *d != EditFlags::Add).count(), a.len());
+        assert_eq!(diffs.iter().filter(|&d| *d != EditFlags::Delete).count(), b.len());
+    }
+
+    #[test]
+    fn test_linear_myers_diffs() {
+        let a = vec![1, 2, 3];
+        let b = vec![1, 5, 3];
+        let diffs = linear_myers_diffs(&a, &b);
+        let expected = vec![EditFlags::Same, EditFlags::Add, EditFlags::Delete, EditFlags::Same];
+        assert_eq!(diffs, expected);
+
+        let a: Vec<_> = "ABCABBA".chars().collect();
+        let b: Vec<_> = "CBABAC".chars().collect();
+        let diffs = linear_myers_diffs(&a, &b);
+        assert_eq!(diffs.iter().filter(|&d| *d != EditFlags::Same).count(), 5);
+    }
+
+    #[test]
+    fn test_diff_with_linear_myers() {
+        let a = "the quick brown fox jumped over the lazy dog";
+        let b = "thequick brown fox juumped over and lazy dog dog";
+        assert_eq!(diff_with(a.as_bytes(), b.as_bytes(), Algorithm::LinearMyers),
+                   diff_with(a.as_bytes(), b.as_bytes(), Algorithm::Lcs));
+    }
+
+    #[test]
+    fn test_patience_diffs() {
+        let a = vec![1, 2, 3];
+        let b = vec![1, 5, 3];
+        let diffs = patience_diffs(&a, &b);
+        let expected = vec![EditFlags::Same, EditFlags::Add, EditFlags::Delete, EditFlags::Same];
+        assert_eq!(diffs, expected);
+    }
+
+    #[test]
+    fn test_diff_with_patience() {
+        let a = vec!["fn a() {", "    one", "}", "", "fn b() {", "    two", "}"];
+        let b = vec!["fn a() {", "    one", "}", "", "fn c() {", "    three", "}", "", "fn b() {",
+                     "    two", "}"];
+        let add = ["fn c() {", "    three", "}", ""];
+        let expected = vec![DiffItem::Add {
+                                start_from: 4,
+                                start_to: 5,
+                                end_to: 9,
+                                items: &add,
+                            }];
+        assert_eq!(diff_with(&a, &b, Algorithm::Patience), expected);
+    }
+
+    #[test]
+    fn test_patience_reordered() {
+        let a = vec!["a", "b", "c", "d", "e"];
+        let b = vec!["d", "e", "x", "a", "b", "c"];
+        let diffs = patience_diffs(&a, &b);
+        assert_eq!(diffs.iter().filter(|&d| *d != EditFlags::Add).count(), a.len());
+        assert_eq!(diffs.iter().filter(|&d| *d != EditFlags::Delete).count(), b.len());
+
+        // later items are patched first so earlier indices stay valid
+        let mut patched = a.clone();
+        for item in diff_with(&a, &b, Algorithm::Patience).iter().rev() {
+            patched = patch(&patched, item);
+        }
+        assert_eq!(patched, b);
+    }
+
+    // the example from "How different are different diff algorithms in Git?"
+    const FROBNITZ_FROM: &str = "#include <stdio.h>
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf(\"Your answer is: \");
+        printf(\"%d\\n\", foo);
+    }
+}
+
+int fact(int n)
+{
+    if(n > 1)
+    {
+        return fact(n-1) * n;
+    }
+    return 1;
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fact(10));
+}";
+
+    const FROBNITZ_TO: &str = "#include <stdio.h>
+
+int fib(int n)
+{
+    if(n > 2)
+    {
+        return fib(n-1) + fib(n-2);
+    }
+    return 1;
+}
+
+// Frobs foo heartily
+int frobnitz(int foo)
+{
+    int i;
+    for(i = 0; i < 10; i++)
+    {
+        printf(\"%d\\n\", foo);
+    }
+}
+
+int main(int argc, char **argv)
+{
+    frobnitz(fib(10));
+}";
+
+    #[test]
+    fn test_histogram_fixture() {
+        let a: Vec<_> = FROBNITZ_FROM.lines().collect();
+        let b: Vec<_> = FROBNITZ_TO.lines().collect();
+        // both match the output of git diff --histogram and git diff --patience
+        let expected = vec![DiffItem::Add {
+                                start_from: 2,
+                                start_to: 3,
+                                end_to: 12,
+                                items: &b[2..11],
+                            },
+                            DiffItem::Delete {
+                                start_from: 9,
+                                end_from: 9,
+                                start_to: 17,
+                                items: &a[8..9],
+                            },
+                            DiffItem::Delete {
+                                start_from: 14,
+                                end_from: 22,
+                                start_to: 21,
+                                items: &a[13..22],
+                            },
+                            DiffItem::Change {
+                                start_from: 25,
+                                end_from: 25,
+                                start_to: 24,
+                                end_to: 24,
+                                from: &a[24..25],
+                                to: &b[23..24],
+                            }];
+        assert_eq!(diff_with(&a, &b, Algorithm::Histogram), expected);
+        assert_eq!(diff_with(&a, &b, Algorithm::Patience), expected);
+    }
+
+    #[test]
+    fn test_histogram_repeated_lines() {
+        // nothing is unique, so patience has no anchors to work with
+        let a = vec!["x", "y", "x", "y", "z", "z"];
+        let b = vec!["y", "x", "y", "z", "z", "x"];
+        let diffs = histogram_diffs(&a, &b);
+        let expected = vec![EditFlags::Delete,
+                            EditFlags::Same,
+                            EditFlags::Same,
+                            EditFlags::Same,
+                            EditFlags::Same,
+                            EditFlags::Same,
+                            EditFlags::Add];
+        assert_eq!(diffs, expected);
+    }
+
+    #[test]
+    fn test_diff_with_myers() {
+        let a = vec!["1", "2", "3"];
+        let b = vec!["1", "3", "4"];
+        assert_eq!(diff_with(&a, &b, Algorithm::Myers), diff(&a, &b));
+    }
+
+    /// Aligns the items of both sequences by position, like a column comparison
+    struct Positional;
+
+    impl<T: PartialEq> DiffAlgorithm<T> for Positional {
+        fn diffs(&self, from: &[T], to: &[T]) -> Vec<EditFlags> {
+            let mut diffs = vec![];
+            for (a, b) in from.iter().zip(to) {
+                if a == b {
+                    diffs.push(EditFlags::Same);
+                } else {
+                    diffs.push(EditFlags::Delete);
+                    diffs.push(EditFlags::Add);
+                }
+            }
+            for _ in to.len()..from.len() {
+                diffs.push(EditFlags::Delete);
+            }
+            for _ in from.len()..to.len() {
+                diffs.push(EditFlags::Add);
+            }
+            diffs
+        }
+    }
+
+    #[test]
+    fn test_diff_using_custom_algorithm() {
+        let a = vec!["1", "2", "3"];
+        let b = vec!["2", "3"];
+        let from = ["1", "2", "3"];
+        let to = ["2", "3"];
+        let expected = vec![DiffItem::Change {
+                                start_from: 1,
+                                end_from: 3,
+                                start_to: 1,
+                                end_to: 2,
+                                from: &from,
+                                to: &to,
+                            }];
+        assert_eq!(diff_using(&a, &b, &Positional), expected);
+        assert_eq!(diff_using(&a, &b, &Lcs), diff(&a, &b));
+    }
+
+    struct Broken;
+
+    impl<T> DiffAlgorithm<T> for Broken {
+        fn diffs(&self, _: &[T], _: &[T]) -> Vec<EditFlags> {
+            vec![EditFlags::Same]
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "DiffAlgorithm returned edits for 1 and 1 items")]
+    fn test_diff_using_incomplete_edits() {
+        diff_using(&[1, 2], &[1], &Broken);
+    }
+
+    #[test]
+    fn test_diff_leading_edits() {
+        let a = vec!["b"];
+        let b = vec!["a", "b"];
+        let add = ["a"];
+        let expected = vec![DiffItem::Add {
+                                start_from: 0,
+                                start_to: 1,
+                                end_to: 2,
+                                items: &add,
+                            }];
+        assert_eq!(diff(&a, &b), expected);
+
+        let del = ["a"];
+        let expected = vec![DiffItem::Delete {
+                                start_from: 1,
+                                end_from: 1,
+                                start_to: 0,
+                                items: &del,
+                            }];
+        assert_eq!(diff(&b, &a), expected);
+    }
+
+    #[test]
+    fn test_diff_large() {
+        let a: Vec<_> = (0..50_000).map(|i| i.to_string()).collect();
+        let mut b = a.clone();
+        b[10] = "ten".to_string();
+        b.remove(30_000);
+        let diffitems = diff(&a, &b);
+        assert_eq!(diffitems.len(), 2);
+        match diffitems[1] {
+            DiffItem::Delete { start_from, .. } => assert_eq!(start_from, 30_001),
+            _ => panic!("expected a delete"),
+        }
+    }
+
+    #[test]
+    fn test_common_prefix_suffix() {
+        let a = vec![1, 2, 3, 4, 5];
+        let b = vec![1, 2, 9, 5];
+        assert_eq!(common_prefix_len(&a, &b), 2);
+        assert_eq!(common_suffix_len(&a, &b), 1);
+        assert_eq!(common_prefix_len(&a, &a[..2]), 2);
+        assert_eq!(common_suffix_len(&a, &[]), 0);
+    }
+
+    #[test]
+    fn test_trimmed_diffs() {
+        let a = vec![1, 2, 3, 4, 5];
+        let b = vec![1, 2, 9, 5];
+        let diffs = trimmed_diffs(&a, &b, |from, to| {
+            assert_eq!(from, &[3, 4]);
+            assert_eq!(to, &[9]);
+            myers_diffs(from, to)
+        });
+        let expected = vec![EditFlags::Same,
+                            EditFlags::Same,
+                            EditFlags::Delete,
+                            EditFlags::Delete,
+                            EditFlags::Add,
+                            EditFlags::Same];
+        assert_eq!(diffs, expected);
+
+        // identical inputs never reach the algorithm with anything to do
+        let diffs = trimmed_diffs(&a, &a, |from, to| {
+            assert!(from.is_empty() && to.is_empty());
+            vec![]
+        });
+        assert_eq!(diffs, vec![EditFlags::Same; 5]);
+    }
+
+    #[test]
+    fn test_intern() {
+        let a = vec!["x", "y", "x"];
+        let b = vec!["y", "z"];
+        let (a_ids, b_ids) = intern(&a, &b);
+        assert_eq!(a_ids, vec![0, 1, 0]);
+        assert_eq!(b_ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_diff_with_long_common_ends() {
+        let a: Vec<_> = (0..200_000).map(|i| format!("line {}", i)).collect();
+        let mut b = a.clone();
+        b[100_000] = "changed".to_string();
+        for algorithm in &[Algorithm::Myers, Algorithm::LinearMyers, Algorithm::Patience,
+                           Algorithm::Histogram] {
+            let to = ["changed".to_string()];
+            let expected = vec![DiffItem::Change {
+                                    start_from: 100_001,
+                                    end_from: 100_001,
+                                    start_to: 100_001,
+                                    end_to: 100_001,
+                                    from: &a[100_000..100_001],
+                                    to: &to,
+                                }];
+            assert_eq!(diff_with(&a, &b, *algorithm), expected);
+        }
+    }
+
+    #[test]
+    fn test_diffitem_ranges() {
+        let a = vec![1, 2, 3];
+        let b = vec![1, 3, 4];
+        let diffitems = diff(&a, &b);
+        assert_eq!((diffitems[0].from_range(), diffitems[0].to_range()), (1..2, 1..1));
+        assert_eq!((diffitems[1].from_range(), diffitems[1].to_range()), (3..3, 2..3));
+        assert_eq!((diffitems[0].removed(), diffitems[0].inserted()), (&[2][..], &[][..]));
+    }
+
+    #[test]
+    fn test_write_unified() {
+        let a: Vec<_> = "a b c d e f g h i j".split(' ').collect();
+        let b: Vec<_> = "x a b d e f g h i j k".split(' ').collect();
+        let mut output = Vec::new();
+        write_unified(&mut output, &a, &b, &diff(&a, &b), 1).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "@@ -1,4 +1,4 @@\n+x\n a\n b\n-c\n d\n@@ -10 +10,2 @@\n j\n+k\n");
+
+        let mut output = Vec::new();
+        write_unified(&mut output, &a, &b, &diff(&a, &b), 0).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "@@ -0,0 +1 @@\n+x\n@@ -3 +3,0 @@\n-c\n@@ -10,0 +11 @@\n+k\n");
+
+        let mut output = Vec::new();
+        write_unified(&mut output, &a, &b, &diff(&a, &b), 2).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "@@ -1,5 +1,5 @@\n+x\n a\n b\n-c\n d\n e\n@@ -9,2 +9,3 @@\n i\n j\n+k\n");
+    }
+
+    #[test]
+    fn test_write_unified_no_changes() {
+        let a = vec!["same"];
+        let mut output = Vec::new();
+        write_unified(&mut output, &a, &a, &diff(&a, &a), 3).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_file_header() {
+        let mut header = FileHeader::new("a.txt");
+        assert_eq!(header.to_string(), "a.txt");
+        header.modified = Some(UNIX_EPOCH + Duration::new(1_489_084_200, 5));
+        assert_eq!(header.to_string(), "a.txt\t2017-03-09 18:30:00.000000005 +0000");
+        header.modified = Some(UNIX_EPOCH + Duration::new(951_782_400, 0));
+        assert_eq!(header.to_string(), "a.txt\t2000-02-29 00:00:00.000000000 +0000");
+        header.modified = Some(UNIX_EPOCH - Duration::new(0, 500_000_000));
+        assert_eq!(header.to_string(), "a.txt\t1969-12-31 23:59:59.500000000 +0000");
+    }
+
+    #[test]
+    fn test_file_header_calendar() {
+        // the unified and context forms of a time, given in seconds since 1970
+        let formats = |secs: i64| {
+            let mut header = FileHeader::new("a");
+            header.modified = Some(if secs < 0 {
+                UNIX_EPOCH - Duration::new(-secs as u64, 0)
+            } else {
+                UNIX_EPOCH + Duration::new(secs as u64, 0)
+            });
+            let mut output = Vec::new();
+            write_context_header(&mut output, &header, &header).unwrap();
+            let context = String::from_utf8(output).unwrap();
+            (header.to_string()[2..21].to_string(), context[6..30].to_string())
+        };
+        let expect = |secs, unified: &str, context: &str| {
+            assert_eq!(formats(secs), (unified.to_string(), context.to_string()));
+        };
+
+        // around the ends of years
+        expect(946_684_799, "1999-12-31 23:59:59", "Fri Dec 31 23:59:59 1999");
+        expect(946_684_800, "2000-01-01 00:00:00", "Sat Jan  1 00:00:00 2000");
+        expect(1_735_689_599, "2024-12-31 23:59:59", "Tue Dec 31 23:59:59 2024");
+        expect(-1, "1969-12-31 23:59:59", "Wed Dec 31 23:59:59 1969");
+        // a leap year divisible by 400, and a century that isn't one
+        expect(951_825_600, "2000-02-29 12:00:00", "Tue Feb 29 12:00:00 2000");
+        expect(951_868_800, "2000-03-01 00:00:00", "Wed Mar  1 00:00:00 2000");
+        expect(4_107_542_399, "2100-02-28 23:59:59", "Sun Feb 28 23:59:59 2100");
+        expect(4_107_542_400, "2100-03-01 00:00:00", "Mon Mar  1 00:00:00 2100");
+        expect(-2_203_891_200, "1900-03-01 00:00:00", "Thu Mar  1 00:00:00 1900");
+    }
+
+    #[test]
+    fn test_write_context() {
+        let a: Vec<_> = "a b c d e f g h i j".split(' ').collect();
+        let b: Vec<_> = "x a b d e f g h i j k".split(' ').collect();
+        let mut output = Vec::new();
+        write_context(&mut output, &a, &b, &diff(&a, &b), 3).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "***************\n*** 1,6 ****\n  a\n  b\n- c\n  d\n  e\n  f\n\
+                    --- 1,6 ----\n+ x\n  a\n  b\n  d\n  e\n  f\n\
+                    ***************\n*** 8,10 ****\n--- 8,11 ----\n  h\n  i\n  j\n+ k\n");
+
+        let a = vec!["a", "b"];
+        let b = vec!["c"];
+        let mut output = Vec::new();
+        write_context(&mut output, &a, &b, &diff(&a, &b), 0).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "***************\n*** 1,2 ****\n! a\n! b\n--- 1 ----\n! c\n");
+        let mut output = Vec::new();
+        write_context(&mut output, &b, &b[..0], &diff(&b, &b[..0]), 0).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "***************\n*** 1 ****\n- c\n--- 0 ----\n");
+    }
+
+    #[test]
+    fn test_write_context_missing_newline() {
+        let write = |from: &[&str], to: &[&str], missing_newline, context| {
+            let options = DiffOptions { missing_newline, ..Default::default() };
+            let diffs = diff_lines(from, to, &options);
+            let mut output = Vec::new();
+            write_context_file(&mut output, from, to, &diffs, context, missing_newline).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        // "a\nb" against "a\nb\n", and the other way round
+        let ab = vec!["a", "b"];
+        assert_eq!(write(&ab, &ab, MissingNewline { from: true, to: false }, 3),
+                   "***************\n*** 1,2 ****\n  a\n! b\n\\ No newline at end of file\n\
+                    --- 1,2 ----\n  a\n! b\n");
+        assert_eq!(write(&ab, &ab, MissingNewline { from: false, to: true }, 3),
+                   "***************\n*** 1,2 ****\n  a\n! b\n\
+                    --- 1,2 ----\n  a\n! b\n\\ No newline at end of file\n");
+
+        // the marker follows a last line shown as context on both sides
+        let from = vec!["a", "b"];
+        let to = vec!["x", "b"];
+        assert_eq!(write(&from, &to, MissingNewline { from: true, to: true }, 1),
+                   "***************\n*** 1,2 ****\n! a\n  b\n\\ No newline at end of file\n\
+                    --- 1,2 ----\n! x\n  b\n\\ No newline at end of file\n");
+    }
+
+    #[test]
+    fn test_write_context_header() {
+        let mut from = FileHeader::new("a.txt");
+        from.modified = Some(UNIX_EPOCH + Duration::new(1_489_084_200, 5));
+        let to = FileHeader::new("b.txt");
+        let mut output = Vec::new();
+        write_context_header(&mut output, &from, &to).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "*** a.txt\tThu Mar  9 18:30:00 2017\n--- b.txt\n");
+    }
+
+    #[test]
+    fn test_write_ed_script() {
+        let a = vec!["a", "b", "c", "d"];
+        let b = vec!["x", "a", "c", "y", "z"];
+        let mut output = Vec::new();
+        write_ed_script(&mut output, &diff(&a, &b)).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "4c\ny\nz\n.\n2d\n0a\nx\n.\n");
+    }
+
+    #[test]
+    fn test_write_ed_script_lone_dot() {
+        let a = vec!["a"];
+        let b = vec!["a", ".", "b", "."];
+        let mut output = Vec::new();
+        write_ed_script(&mut output, &diff(&a, &b)).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "1a\n..\n.\ns/.//\na\nb\n..\n.\ns/.//\n");
+    }
+
+    #[test]
+    fn test_write_rcs() {
+        let a = vec!["a", "b", "c", "d"];
+        let b = vec!["x", "a", "c", "y", "z"];
+        let mut output = Vec::new();
+        write_rcs(&mut output, &diff(&a, &b)).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "a0 1\nx\nd2 1\nd4 1\na4 2\ny\nz\n");
+    }
+
+    #[test]
+    fn test_write_side_by_side() {
+        let a = vec!["a", "b\tTAB", "c", "d"];
+        let b = vec!["a", "B", "c", "new", "d"];
+        let options = SideBySideOptions { width: 40, ..Default::default() };
+        let mut output = Vec::new();
+        write_side_by_side(&mut output, &a, &b, &diff(&a, &b), &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "a                     a\n\
+                    b       TAB        |  B\n\
+                    c                     c\n\
+                    \x20                  >  new\n\
+                    d                     d\n");
+    }
+
+    #[test]
+    fn test_write_side_by_side_options() {
+        let a = vec!["a", "b", "c"];
+        let b = vec!["a", "c", "a long line that gets cut off"];
+        let diffs = diff(&a, &b);
+
+        let mut options = SideBySideOptions { width: 30, left_column: true, ..Default::default() };
+        let mut output = Vec::new();
+        write_side_by_side(&mut output, &a, &b, &diffs, &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "a             (\nb             <\nc             (\n\
+                    \x20             >  a long line t\n");
+
+        options.suppress_common_lines = true;
+        let mut output = Vec::new();
+        write_side_by_side(&mut output, &a, &b, &diffs, &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "b             <\n              >  a long line t\n");
+    }
+
+    #[test]
+    fn test_write_side_by_side_partial_script() {
+        // a script that doesn't account for every difference between the files
+        let a = vec!["a", "b", "c"];
+        let b = vec!["a", "c", "d", "e"];
+        let diffs = diff(&a[..2], &b[..1]);
+        let options = SideBySideOptions { width: 30, ..Default::default() };
+        let mut output = Vec::new();
+        write_side_by_side(&mut output, &a, &b, &diffs, &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "a                a\nb             <\nc                c\n\
+                    \x20                d\n                 e\n");
+    }
+
+    #[test]
+    fn test_parse_unified_round_trip() {
+        let mut random = Lcg(54_321);
+        for _ in 0..300 {
+            let alphabet = random.below(5) + 1;
+            let a: Vec<_> = random.sequence(25, alphabet).iter().map(u64::to_string).collect();
+            let b: Vec<_> = random.sequence(25, alphabet).iter().map(u64::to_string).collect();
+            let context = random.below(4) as usize;
+            let mut output = Vec::new();
+            let (header_a, header_b) = (FileHeader::new("a"), FileHeader::new("b"));
+            write_unified_header(&mut output, &header_a, &header_b).unwrap();
+            write_unified(&mut output, &a, &b, &diff(&a, &b), context).unwrap();
+
+            let files = parse_unified(&String::from_utf8(output).unwrap()).unwrap();
+            assert_eq!(files.len(), 1);
+            let mut patched: Vec<&str> = Vec::new();
+            let mut line = 0;
+            for hunk in &files[0].hunks {
+                assert_eq!(hunk.from_lines(), a[hunk.from.clone()].to_vec());
+                assert_eq!(hunk.to_lines(), b[hunk.to.clone()].to_vec());
+                patched.extend(a[line..hunk.from.start].iter().map(String::as_str));
+                patched.extend(hunk.to_lines());
+                line = hunk.from.end;
+            }
+            patched.extend(a[line..].iter().map(String::as_str));
+            assert_eq!(patched, b);
+        }
+    }
+
+    #[test]
+    fn test_write_unified_missing_newline_round_trip() {
+        let mut random = Lcg(808);
+        for _ in 0..300 {
+            // texts that may or may not end with a newline
+            let mut text = || {
+                let lines: Vec<_> = random.sequence(6, 3).iter().map(u64::to_string).collect();
+                let mut text = lines.join("\n");
+                if !lines.is_empty() && random.below(2) == 0 {
+                    text.push('\n');
+                }
+                text
+            };
+            let (a, b) = (text(), text());
+            let split = |text: &str| -> (Vec<String>, bool) {
+                let missing_newline = !text.is_empty() && !text.ends_with('\n');
+                let mut lines: Vec<_> = text.split('\n').map(str::to_string).collect();
+                if !missing_newline {
+                    lines.pop();
+                }
+                (lines, missing_newline)
+            };
+            let ((a_lines, a_missing), (b_lines, b_missing)) = (split(&a), split(&b));
+            let missing_newline = MissingNewline { from: a_missing, to: b_missing };
+            let options = DiffOptions { missing_newline, ..Default::default() };
+            let diffs = diff_lines(&a_lines, &b_lines, &options);
+            let context = random.below(3) as usize;
+            let mut output = Vec::new();
+            write_unified_header(&mut output, &FileHeader::new("a"), &FileHeader::new("b"))
+                .unwrap();
+            write_unified_file(&mut output, &a_lines, &b_lines, &diffs, context, missing_newline)
+                .unwrap();
+
+            // apply it the way rust-patch does
+            let files = parse_unified(&String::from_utf8(output).unwrap()).unwrap();
+            let hunks = files.first().map_or(vec![], |file| file.hunks.clone());
+            let applied = apply_hunks(&a_lines, &hunks, 0);
+            assert_eq!(applied.lines, b_lines);
+            let patched_missing = applied.missing_newline.unwrap_or(a_missing);
+            let mut patched = applied.lines.join("\n");
+            if !applied.lines.is_empty() && !patched_missing {
+                patched.push('\n');
+            }
+            assert_eq!(patched, b, "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_parse_unified_git() {
+        let text = "diff --git a/x.txt b/x.txt\n\
+                    index 1234567..89abcde 100644\n\
+                    --- a/x.txt\t2017-03-09 18:30:00.000000000 +0000\n\
+                    +++ b/x.txt\n\
+                    @@ -1,2 +1,2 @@ fn main()\n \
+                    one\n\
+                    -two\n\
+                    \\ No newline at end of file\n\
+                    +three\n\
+                    diff --git a/new.txt b/new.txt\n\
+                    --- /dev/null\n\
+                    +++ b/new.txt\n\
+                    @@ -0,0 +1 @@\n\
+                    +new\n";
+        let files = parse_unified(text).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].from_path, "a/x.txt");
+        assert_eq!(files[0].hunks,
+                   vec![PatchHunk {
+                            from: 0..2,
+                            to: 0..2,
+                            lines: vec![PatchLine::Context("one".to_string()),
+                                        PatchLine::Removed("two".to_string()),
+                                        PatchLine::Added("three".to_string())],
+                            from_missing_newline: true,
+                            to_missing_newline: false,
+                        }]);
+        assert_eq!(files[1].from_path, "/dev/null");
+        assert_eq!(files[1].hunks[0].from, 0..0);
+        assert_eq!(files[1].hunks[0].to, 0..1);
+    }
+
+    #[test]
+    fn test_parse_unified_errors() {
+        let error = |text: &str| {
+            let error = parse_unified(text).unwrap_err();
+            (error.line, error.column)
+        };
+        assert_eq!(error("--- a\n+++ b\n@@ -1,x +1 @@\n"), (3, 7));
+        assert_eq!(error("--- a\n+++ b\n@@ -1 +1\n"), (3, 9));
+        assert_eq!(error("--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n"), (5, 1));
+        assert_eq!(error("--- a\n+++ b\n@@ -1 +1 @@\n*a\n"), (4, 1));
+        assert_eq!(error("--- a\n+++ b\n@@ -1 +1 @@\n-a\n-b\n"), (5, 1));
+        assert_eq!(error("--- a\nb\n"), (2, 1));
+        assert_eq!(error("@@ -1 +1 @@\n"), (1, 1));
+        assert_eq!(parse_unified("--- a\n+++ b\n@@ -0 +1 @@\n").unwrap_err().to_string(),
+                   "line 3, column 6: line numbers start at 1");
+
+        // ranges that run past the largest line number, on either side
+        let max = usize::MAX;
+        let too_large = |text: String| parse_unified(&text).unwrap_err().to_string();
+        assert_eq!(too_large(format!("--- a\n+++ b\n@@ -5,{} +1 @@\n", max)),
+                   format!("line 3, column {}: range too large", 7 + max.to_string().len()));
+        assert_eq!(too_large(format!("--- a\n+++ b\n@@ -1 +2,{} @@\n", max)),
+                   format!("line 3, column {}: range too large", 10 + max.to_string().len()));
+    }
+
+    #[test]
+    fn test_parse_normal_round_trip() {
+        let mut random = Lcg(99);
+        for _ in 0..300 {
+            let alphabet = random.below(5) + 1;
+            let a = random.sequence(25, alphabet);
+            let b = random.sequence(25, alphabet);
+            let diffs = diff_with(&a, &b, Algorithm::Myers);
+            let text: String = diffs.iter().map(|edit| edit.to_string()).collect();
+
+            let hunks = parse_normal(&text).unwrap();
+            assert_eq!(hunks.len(), diffs.len());
+            for (hunk, item) in hunks.iter().zip(&diffs) {
+                assert_eq!(hunk.from, item.from_range());
+                assert_eq!(hunk.to, item.to_range());
+                let removed: Vec<_> = item.removed().iter().map(u64::to_string).collect();
+                let inserted: Vec<_> = item.inserted().iter().map(u64::to_string).collect();
+                assert_eq!(hunk.from_lines(), removed);
+                assert_eq!(hunk.to_lines(), inserted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_normal_errors() {
+        let error = |text: &str| {
+            let error = parse_normal(text).unwrap_err();
+            (error.line, error.column)
+        };
+        assert_eq!(error("1,2x3\n"), (1, 4));
+        assert_eq!(error("1,2c\n"), (1, 5));
+        assert_eq!(error("2c2\n< a\n> b\n"), (3, 1));
+        assert_eq!(error("2c2\n< a\n---\n"), (4, 1));
+        assert_eq!(error("2,1d0\n"), (1, 4));
+        assert_eq!(error("1a1 extra\n> a\n"), (1, 4));
+        assert_eq!(error("1,2a1\n> a\n"), (1, 1));
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        let a = vec!["a", "b", "c", "d", "e", "f", "g"];
+        let b = vec!["a", "B", "c", "d", "e", "F", "g", "h"];
+        let mut output = Vec::new();
+        write_unified_header(&mut output, &FileHeader::new("a"), &FileHeader::new("b")).unwrap();
+        write_unified(&mut output, &a, &b, &diff(&a, &b), 1).unwrap();
+        let hunks = parse_unified(&String::from_utf8(output).unwrap()).unwrap()[0].hunks.clone();
+        assert_eq!(hunks.len(), 2);
+
+        let applied = apply_hunks(&a, &hunks, 0);
+        assert_eq!(applied.lines, b);
+        assert_eq!(applied.outcomes, vec![HunkOutcome::Applied, HunkOutcome::Applied]);
+
+        let inverted: Vec<_> = hunks.iter().map(PatchHunk::invert).collect();
+        assert_eq!(apply_hunks(&b, &inverted, 0).lines, a);
+
+        // the first hunk no longer matches, so only the second is applied
+        let changed = vec!["a", "x", "c", "d", "e", "f", "g"];
+        let applied = apply_hunks(&changed, &hunks, 0);
+        assert_eq!(applied.lines, vec!["a", "x", "c", "d", "e", "F", "g", "h"]);
+        assert_eq!(applied.outcomes, vec![HunkOutcome::Rejected, HunkOutcome::Applied]);
+        assert_eq!(applied.rejected(), vec![0]);
+    }
+
+    #[test]
+    fn test_patch_hunk_display() {
+        let text = "--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+c\n";
+        let hunk = &parse_unified(text).unwrap()[0].hunks[0];
+        assert_eq!(hunk.to_string(), &text[12..]);
+        assert_eq!(hunk.invert().to_string(),
+                   "@@ -1,2 +1,2 @@\n a\n+b\n\\ No newline at end of file\n-c\n");
+    }
+
+    #[test]
+    fn test_apply_hunks_fuzzy() {
+        let a: Vec<_> = (1..31).map(|i| i.to_string()).collect();
+        let mut b = a.clone();
+        b[4] = "five".to_string();
+        b[24] = "twentyfive".to_string();
+        let mut output = Vec::new();
+        write_unified_header(&mut output, &FileHeader::new("a"), &FileHeader::new("b")).unwrap();
+        write_unified(&mut output, &a, &b, &diff(&a, &b), 3).unwrap();
+        let hunks = parse_unified(&String::from_utf8(output).unwrap()).unwrap()[0].hunks.clone();
+
+        // three lines added at the top move both hunks
+        let mut moved = vec!["x".to_string(), "y".to_string(), "z".to_string()];
+        moved.extend(a.iter().cloned());
+        let applied = apply_hunks(&moved, &hunks, 2);
+        assert_eq!(applied.outcomes, vec![HunkOutcome::Offset(3), HunkOutcome::Offset(3)]);
+        assert_eq!(applied.lines[3..], b[..]);
+
+        // a changed context line needs fuzz, as much as its distance from the
+        // end of the hunk
+        let mut drifted = moved.clone();
+        drifted[5] = "three".to_string();
+        drifted[30] = "twentyeight".to_string();
+        let applied = apply_hunks(&drifted, &hunks, 2);
+        assert_eq!(applied.outcomes,
+                   vec![HunkOutcome::Fuzz { fuzz: 2, offset: 3 },
+                        HunkOutcome::Fuzz { fuzz: 1, offset: 3 }]);
+        assert_eq!(applied.lines[7], "five");
+        assert_eq!(applied.lines[27], "twentyfive");
+        assert_eq!(apply_hunks(&drifted, &hunks, 1).outcomes,
+                   vec![HunkOutcome::Rejected, HunkOutcome::Fuzz { fuzz: 1, offset: 3 }]);
+        assert_eq!(apply_hunks(&drifted, &hunks, 0).rejected(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_apply_hunks_file_ends() {
+        // a hunk with less context before it than after must be at the start
+        let text = "--- a\n+++ b\n@@ -1,3 +1,3 @@\n-a\n+A\n b\n c\n";
+        let hunks = parse_unified(text).unwrap()[0].hunks.clone();
+        assert_eq!(apply_hunks(&["a", "b", "c", "d"], &hunks, 2).lines, vec!["A", "b", "c", "d"]);
+        assert_eq!(apply_hunks(&["x", "a", "b", "c"], &hunks, 1).rejected(), vec![0]);
+        // unless all the context after it is dropped too
+        assert_eq!(apply_hunks(&["x", "a", "b", "c"], &hunks, 2).outcomes,
+                   vec![HunkOutcome::Fuzz { fuzz: 2, offset: 1 }]);
+        assert_eq!(apply_hunks(&["a", "b", "x"], &hunks, 1).outcomes,
+                   vec![HunkOutcome::Fuzz { fuzz: 1, offset: 0 }]);
+
+        // and one with less after it must be at the end
+        let text = "--- a\n+++ b\n@@ -2,2 +2,2 @@\n b\n-c\n+C\n";
+        let hunks = parse_unified(text).unwrap()[0].hunks.clone();
+        assert_eq!(apply_hunks(&["x", "a", "b", "c"], &hunks, 2).outcomes,
+                   vec![HunkOutcome::Offset(1)]);
+        assert_eq!(apply_hunks(&["a", "b", "c", "d"], &hunks, 0).rejected(), vec![0]);
+        assert_eq!(apply_hunks(&["a", "b", "c", "d"], &hunks, 2).outcomes,
+                   vec![HunkOutcome::Fuzz { fuzz: 1, offset: 0 }]);
+    }
+
+    #[test]
+    fn test_apply_hunks_missing_newline() {
+        let text = "--- a\n+++ b\n@@ -2,2 +2,2 @@\n b\n-c\n+C\n\\ No newline at end of file\n";
+        let hunks = parse_unified(text).unwrap()[0].hunks.clone();
+        let applied = apply_hunks(&["a", "b", "c"], &hunks, 0);
+        assert_eq!(applied.missing_newline, Some(true));
+        // where the hunk was applied counts, not where it said it goes
+        let applied = apply_hunks(&["x", "a", "b", "c"], &hunks, 0);
+        assert_eq!(applied.outcomes, vec![HunkOutcome::Offset(1)]);
+        assert_eq!(applied.missing_newline, Some(true));
+        let applied = apply_hunks(&["a", "x", "c", "d"], &hunks, 1);
+        assert_eq!(applied.outcomes, vec![HunkOutcome::Fuzz { fuzz: 1, offset: 0 }]);
+        assert_eq!(applied.missing_newline, None);
+        // nor does a hunk whose last line was dropped for fuzz
+        let text = "--- a\n+++ b\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n\\ No newline at end of file\n";
+        let hunks = parse_unified(text).unwrap()[0].hunks.clone();
+        let applied = apply_hunks(&["a", "b", "x"], &hunks, 1);
+        assert_eq!(applied.lines, vec!["a", "B", "x"]);
+        assert_eq!(applied.missing_newline, None);
+
+        // "\r" is part of a line, not of its ending
+        let text = "--- a\n+++ b\n@@ -1 +1 @@\n-a\r\n+b\r\n";
+        let hunks = parse_unified(text).unwrap()[0].hunks.clone();
+        assert_eq!(apply_hunks(&["a\r"], &hunks, 0).lines, vec!["b\r"]);
+        assert_eq!(apply_hunks(&["a"], &hunks, 0).rejected(), vec![0]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = vec!["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let ours = vec!["1", "TWO", "3", "4", "5", "six", "7", "8", "9"];
+        let theirs = vec!["1", "2", "3", "four", "5", "SIX", "7", "8", "9", "ten"];
+        assert_eq!(merge(&base, &ours, &theirs),
+                   vec![MergeChunk::Unchanged(&base[..1]),
+                        MergeChunk::Ours {
+                            base: &base[1..2],
+                            ours: &ours[1..2],
+                        },
+                        MergeChunk::Unchanged(&base[2..3]),
+                        MergeChunk::Theirs {
+                            base: &base[3..4],
+                            theirs: &theirs[3..4],
+                        },
+                        MergeChunk::Unchanged(&base[4..5]),
+                        MergeChunk::Conflict {
+                            base: &base[5..6],
+                            ours: &ours[5..6],
+                            theirs: &theirs[5..6],
+                        },
+                        MergeChunk::Unchanged(&base[6..]),
+                        MergeChunk::Theirs {
+                            base: &[],
+                            theirs: &theirs[9..],
+                        }]);
+    }
+
+    #[test]
+    fn test_merge_overlapping_changes() {
+        let base = vec!["a", "b", "c", "d"];
+
+        // the same change on both sides isn't a conflict
+        let same = vec!["a", "x", "c", "d"];
+        assert_eq!(merge(&base, &same, &same)[1],
+                   MergeChunk::Both {
+                       base: &base[1..2],
+                       merged: &same[1..2],
+                   });
+
+        // changes to neighbouring lines are
+        let ours = vec!["a", "x", "c", "d"];
+        let theirs = vec!["a", "b", "y", "d"];
+        assert_eq!(merge(&base, &ours, &theirs)[1],
+                   MergeChunk::Conflict {
+                       base: &base[1..3],
+                       ours: &ours[1..3],
+                       theirs: &theirs[1..3],
+                   });
+
+        // as are different insertions at the same place
+        let ours = vec!["a", "b", "x", "c", "d"];
+        let theirs = vec!["a", "b", "y", "c", "d"];
+        assert_eq!(merge(&base, &ours, &theirs)[1],
+                   MergeChunk::Conflict {
+                       base: &[],
+                       ours: &ours[2..3],
+                       theirs: &theirs[2..3],
+                   });
+    }
+
+    #[test]
+    fn test_merge_one_side() {
+        let mut random = Lcg(7);
+        for _ in 0..200 {
+            let base = random.sequence(20, 4);
+            let side = random.sequence(20, 4);
+            let resolved = |chunks: Vec<MergeChunk<u64>>| -> Vec<u64> {
+                chunks.iter().flat_map(|chunk| chunk.resolved().unwrap().to_vec()).collect()
+            };
+            assert_eq!(resolved(merge(&base, &side, &base)), side);
+            assert_eq!(resolved(merge(&base, &base, &side)), side);
+            assert_eq!(resolved(merge(&base, &side, &side)), side);
+        }
+    }
+
+    #[test]
+    fn test_write_merge() {
+        let base = vec!["a", "b", "c", "d"];
+        let ours = vec!["a", "x", "c"];
+        let theirs = vec!["a", "b", "c", "e"];
+        let labels = MergeLabels {
+            ours: "mine",
+            base: "old",
+            theirs: "yours",
+        };
+        let mut output = Vec::new();
+        write_merge(&mut output, &merge(&base, &ours, &theirs), &labels).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "a\nx\nc\n<<<<<<< mine\n||||||| old\nd\n=======\ne\n>>>>>>> yours\n");
+    }
+
+    #[test]
+    fn test_diff_words() {
+        assert_eq!(diff_words("", ""), vec![]);
+        assert_eq!(diff_words("", "new words"), vec![(EditFlags::Add, "new words")]);
+        assert_eq!(diff_words("a_b, c", "a_b; c"),
+                   vec![(EditFlags::Same, "a_b"),
+                        (EditFlags::Delete, ","),
+                        (EditFlags::Add, ";"),
+                        (EditFlags::Same, " c")]);
+        assert_eq!(diff_words("naïve café", "naïve cafe"),
+                   vec![(EditFlags::Same, "naïve "),
+                        (EditFlags::Delete, "café"),
+                        (EditFlags::Add, "cafe")]);
+
+        // the pieces rebuild both strings
+        let (from, to) = ("one two three four", "one 2 three four five");
+        let words = diff_words(from, to);
+        let old: String = words.iter().filter(|w| w.0 != EditFlags::Add).map(|w| w.1).collect();
+        let new: String = words.iter().filter(|w| w.0 != EditFlags::Delete).map(|w| w.1).collect();
+        assert_eq!((old.as_str(), new.as_str()), (from, to));
+    }
+
+    #[test]
+    fn test_write_word_diff() {
+        let a = vec!["keep", "gone", "same", "change this line", "end"];
+        let b = vec!["keep", "same", "change that line", "end", "added"];
+        let mut output = Vec::new();
+        write_word_diff(&mut output, &a, &b, &diff(&a, &b), 1, WordStyle::Markup).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "@@ -1,5 +1,5 @@\nkeep\n[-gone-]\nsame\nchange [-this-]{+that+} line\nend\n\
+                    {+added+}\n");
+
+        let mut output = Vec::new();
+        write_word_diff(&mut output, &a[3..4], &b[2..3], &diff(&a[3..4], &b[2..3]), 0,
+                        WordStyle::Color)
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   format!("@@ -1 +1 @@\nchange {}{} line\n", "this".red(), "that".green()));
+    }
+
+    #[test]
+    fn test_diff_chars() {
+        // a combining accent stays with its letter
+        assert_eq!(diff_chars("cafe\u{301}", "cafe"),
+                   vec![(EditFlags::Same, "caf"),
+                        (EditFlags::Delete, "e\u{301}"),
+                        (EditFlags::Add, "e")]);
+        assert_eq!(diff_chars("", "ab"), vec![(EditFlags::Add, "ab")]);
+    }
+
+    #[test]
+    fn test_refine_line() {
+        assert_eq!(refine_line("", ""), Some(vec![]));
+        assert_eq!(refine_line("abcd", "abxy"),
+                   Some(vec![(EditFlags::Same, "ab"),
+                             (EditFlags::Delete, "cd"),
+                             (EditFlags::Add, "xy")]));
+        // 2 of 5 characters in common on each side is too few
+        assert_eq!(refine_line("abcde", "abxyz"), None);
+        assert_eq!(refine_line("abc", ""), None);
+    }
+
+    #[test]
+    fn test_pretty_print_refined() {
+        let from = vec!["keep", "let x = 1;", "gone entirely"];
+        let to = vec!["keep", "let y = 1;", "something new"];
+        let mut output = Vec::new();
+        pretty_print(&mut output, &from, &diff(&from, &to)[0]);
+
+        let (delete, add) = (EditFlags::Delete.to_string(), EditFlags::Add.to_string());
+        let expected = format!("keep\n{} {}{}{}\n{} {}\n{} {}{}{}\n{} {}\n",
+                               delete.red(),
+                               "let ".red(),
+                               "x".red().reversed(),
+                               " = 1;".red(),
+                               delete.red(),
+                               "gone entirely".red(),
+                               add.green(),
+                               "let ".green(),
+                               "y".green().reversed(),
+                               " = 1;".green(),
+                               add.green(),
+                               "something new".green());
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_whitespace_normalize() {
+        let line = "\tif  (a ==\tb) {  ";
+        assert_eq!(Whitespace::Exact.normalize(line), line);
+        assert_eq!(Whitespace::IgnoreTrailing.normalize(line), "\tif  (a ==\tb) {");
+        assert_eq!(Whitespace::IgnoreAmount.normalize(line), " if (a == b) {");
+        assert_eq!(Whitespace::IgnoreAll.normalize(line), "if(a==b){");
+        assert_eq!(Whitespace::IgnoreAmount.normalize(" \t "), "");
+
+        // each setting ignores at least as much as the ones before it
+        let settings = [Whitespace::Exact,
+                        Whitespace::IgnoreTrailing,
+                        Whitespace::IgnoreAmount,
+                        Whitespace::IgnoreAll];
+        assert!(settings.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(Whitespace::IgnoreAll.max(Whitespace::IgnoreAmount), Whitespace::IgnoreAll);
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let from = vec!["a", "b  c", "d ", "e"];
+        let to = vec!["a", "b c", "d", "ef"];
+
+        // with exact comparison it is the same as diff
+        assert_eq!(diff_lines(&from, &to, &DiffOptions::default()).items, diff(&from, &to));
+
+        let trailing = DiffOptions { whitespace: Whitespace::IgnoreTrailing, ..Default::default() };
+        let amount = DiffOptions { whitespace: Whitespace::IgnoreAmount, ..Default::default() };
+        assert_eq!(diff_lines(&from, &to, &trailing).len(), 2);
+        let changes = diff_lines(&from, &to, &amount);
+        assert_eq!(changes.items,
+                   vec![DiffItem::Change {
+                            start_from: 4,
+                            end_from: 4,
+                            start_to: 4,
+                            end_to: 4,
+                            from: &from[3..],
+                            to: &to[3..],
+                        }]);
+        // the lines that matched once normalized keep their original text
+        assert_eq!(patch_all(&from, &changes), Ok(vec!["a", "b  c", "d ", "ef"]));
+
+        let all = DiffOptions { whitespace: Whitespace::IgnoreAll, ..Default::default() };
+        assert_eq!(diff_lines(&["a b"], &["ab"], &all).items, vec![]);
+        assert_eq!(diff_lines(&["a b"], &["ab"], &amount).len(), 1);
+    }
+
+    #[test]
+    fn test_diff_lines_blank() {
+        let from = vec!["a", "", "b", "c", "d"];
+        let to = vec!["a", "b", "  ", "c", "x"];
+        let blank = DiffOptions { ignore_blank_lines: true, ..Default::default() };
+        let changes = diff_lines(&from, &to, &blank);
+        // "  " isn't blank unless whitespace is ignored, and a change to
+        // non-blank lines is kept
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].inserted(), &["  "]);
+        assert_eq!(changes[1].removed(), &["d"]);
+
+        let blank = DiffOptions { whitespace: Whitespace::IgnoreTrailing, ..blank };
+        let changes = diff_lines(&from, &to, &blank);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].removed(), &["d"]);
+    }
+
+    #[test]
+    fn test_diff_lines_blank_side_by_side() {
+        // the blank line that -B leaves out is shown unmarked, and the rows
+        // after it still pair up the same lines
+        let from = vec!["a", "", "b", "c"];
+        let to = vec!["a", "b", "x"];
+        let options = DiffOptions { ignore_blank_lines: true, ..Default::default() };
+        let changes = diff_lines(&from, &to, &options);
+        let side_by_side = SideBySideOptions { width: 30, ..Default::default() };
+        let mut output = Vec::new();
+        write_side_by_side(&mut output, &from, &to, &changes, &side_by_side).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "a                a\n\nb                b\nc             |  x\n");
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Record {
+        id: u32,
+        name: &'static str,
+    }
+
+    #[test]
+    fn test_diff_by() {
+        let record = |id, name| Record { id, name };
+        let from = vec![record(1, "a"), record(2, "b"), record(3, "c")];
+        let to = vec![record(1, "A"), record(3, "c"), record(4, "d")];
+
+        let by_id = diff_by(&from, &to, |a, b| a.id == b.id);
+        assert_eq!(by_id,
+                   vec![DiffItem::Delete {
+                            start_from: 2,
+                            end_from: 2,
+                            start_to: 1,
+                            items: &from[1..2],
+                        },
+                        DiffItem::Add {
+                            start_from: 3,
+                            start_to: 3,
+                            end_to: 4,
+                            items: &to[2..],
+                        }]);
+        // record 1 was renamed, but it has the same id
+        assert_eq!(diff(&from, &to)[0].removed(), &from[..2]);
+        assert_eq!(diff_by_key(&from, &to, |record| record.id), by_id);
+
+        // with plain equality they are the same as other diffs
+        let mut random = Lcg(21);
+        for _ in 0..200 {
+            let a = random.sequence(15, 4);
+            let b = random.sequence(15, 4);
+            assert_eq!(diff_by(&a, &b, |x, y| x == y), diff_with(&a, &b, Algorithm::LinearMyers));
+            assert_eq!(diff_by_key(&a, &b, |&x| x), diff(&a, &b));
+        }
+    }
+
+    #[test]
+    fn test_diff_lines_ignore_case() {
+        let from = vec!["Hello", "World"];
+        let to = vec!["hello", "WORLD", "!"];
+        let options = DiffOptions { ignore_case: true, ..Default::default() };
+        let changes = diff_lines(&from, &to, &options);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].inserted(), &["!"]);
+    }
+
+    #[test]
+    fn test_diff_lines_ignore_matching() {
+        let from = vec!["1", "2", "3", "4", "5", "6"];
+        let with_options = |to: &[&'static str], context| {
+            let options = DiffOptions {
+                ignore_matching_lines: vec![regex::Regex::new("^#").unwrap()],
+                context,
+                ..Default::default()
+            };
+            diff_lines(&from, to, &options)
+                .iter()
+                .map(|item| item.inserted()[0])
+                .collect::<Vec<_>>()
+        };
+
+        // a change to ignored lines is dropped unless it shares a hunk with
+        // one that isn't, which it does if it is within twice the context
+        // before it...
+        let to = vec!["1", "# note", "2", "3", "x", "4", "5", "6"];
+        assert_eq!(with_options(&to, 0), vec!["x"]);
+        assert_eq!(with_options(&to, 1), vec!["# note", "x"]);
+
+        // ...but only if it is within the context after it
+        let to = vec!["1", "2", "3", "x", "4", "# note", "5", "6"];
+        assert_eq!(with_options(&to, 1), vec!["x"]);
+        assert_eq!(with_options(&to, 2), vec!["x", "# note"]);
+
+        let to = vec!["# a", "1", "2", "3", "4", "5", "6", "# b"];
+        assert_eq!(with_options(&to, 3), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_diff_lines_ignored_hunks() {
+        let from = vec!["b", "b", "a", "c"];
+        let write = |to: &[&'static str], options: &DiffOptions, unified: bool| {
+            let diffs = diff_lines(&from, to, options);
+            let mut output = Vec::new();
+            if unified {
+                write_unified(&mut output, &from, to, &diffs, options.context).unwrap();
+            } else {
+                write_context(&mut output, &from, to, &diffs, options.context).unwrap();
+            }
+            String::from_utf8(output).unwrap()
+        };
+
+        // the ignored change between the two hunks is left out, so they stay
+        // apart even though their context would let them join
+        let to = vec!["c", "b", "x", "b", "x", "a"];
+        let matching = DiffOptions {
+            ignore_matching_lines: vec![regex::Regex::new("^x$").unwrap()],
+            context: 1,
+            ..Default::default()
+        };
+        assert_eq!(write(&to, &matching, true),
+                   "@@ -1 +1,2 @@\n+c\n b\n@@ -2,3 +4,3 @@\n b\n+x\n a\n-c\n");
+        assert_eq!(write(&to, &matching, false),
+                   "***************\n*** 1 ****\n--- 1,2 ----\n+ c\n  b\n\
+                    ***************\n*** 2,4 ****\n  b\n  a\n- c\n\
+                    --- 4,6 ----\n  b\n+ x\n  a\n");
+
+        // -B hides blank lines the same way, and context output doesn't
+        // show the hidden one as context
+        let to = vec!["c", "b", "", "b", "", "a"];
+        let blank = DiffOptions { ignore_blank_lines: true, context: 1, ..Default::default() };
+        assert_eq!(write(&to, &blank, true),
+                   "@@ -1 +1,2 @@\n+c\n b\n@@ -2,3 +4,3 @@\n b\n+\n a\n-c\n");
+        assert_eq!(write(&to, &blank, false),
+                   "***************\n*** 1 ****\n--- 1,2 ----\n+ c\n  b\n\
+                    ***************\n*** 2,4 ****\n  b\n  a\n- c\n\
+                    --- 4,6 ----\n  b\n+ \n  a\n");
+
+        // an ignored change just past the context of one hunk starts the
+        // next, whose context overlaps it, as in GNU diff; patch allows that
+        let from = vec!["1", "2", "3", "4", "5"];
+        let to = vec!["Y", "2", "x", "3", "5"];
+        let diffs = diff_lines(&from, &to, &matching);
+        let mut output = Vec::new();
+        write_unified_header(&mut output, &FileHeader::new("a"), &FileHeader::new("b")).unwrap();
+        write_unified(&mut output, &from, &to, &diffs, 1).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(&output[12..],
+                   "@@ -1,2 +1,2 @@\n-1\n+Y\n 2\n@@ -2,4 +2,4 @@\n 2\n+x\n 3\n-4\n 5\n");
+        let applied = apply_hunks(&from, &parse_unified(&output).unwrap()[0].hunks, 0);
+        assert_eq!(applied.lines, to);
+        assert_eq!(applied.outcomes, vec![HunkOutcome::Applied, HunkOutcome::Applied]);
+    }
+
+    #[test]
+    fn test_diff_lines_ignored_unified_round_trip() {
+        let mut random = Lcg(2_020);
+        let x = regex::Regex::new("^x$").unwrap();
+        for _ in 0..500 {
+            let mut lines = || -> Vec<&str> {
+                random.sequence(8, 4).iter().map(|&n| ["a", "b", "", "x"][n as usize]).collect()
+            };
+            let (from, to) = (lines(), lines());
+            let options = DiffOptions {
+                ignore_blank_lines: random.below(2) == 0,
+                ignore_matching_lines: if random.below(2) == 0 {
+                    vec![x.clone()]
+                } else {
+                    vec![]
+                },
+                context: random.below(3) as usize,
+                ..Default::default()
+            };
+            let diffs = diff_lines(&from, &to, &options);
+            let mut output = Vec::new();
+            write_unified_header(&mut output, &FileHeader::new("a"), &FileHeader::new("b"))
+                .unwrap();
+            write_unified_file(&mut output,
+                               &from,
+                               &to,
+                               &diffs,
+                               options.context,
+                               MissingNewline::default())
+                .unwrap();
+
+            // every hunk applies where it says and makes the changes shown
+            let files = parse_unified(&String::from_utf8(output).unwrap()).unwrap();
+            let hunks = files.first().map_or(vec![], |file| file.hunks.clone());
+            let applied = apply_hunks(&from, &hunks, 0);
+            assert!(applied.outcomes.iter().all(|outcome| *outcome == HunkOutcome::Applied));
+            assert_eq!(applied.lines, patch_all(&from, &diffs).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_diff_lines_ignore_matching_side_by_side() {
+        let from = vec!["# one", "a", "# two", "B"];
+        let to = vec!["A", "b", "# three"];
+        let options = DiffOptions {
+            ignore_case: true,
+            ignore_matching_lines: vec![regex::Regex::new("^#").unwrap()],
+            ..Default::default()
+        };
+        assert!(diff_lines(&from, &to, &options).is_empty());
+
+        // lines that only match once case is ignored still share a row
+        let changes = diff_lines_marked(&from, &to, &options);
+        let side_by_side = SideBySideOptions { width: 30, ..Default::default() };
+        let mut output = Vec::new();
+        write_side_by_side_marked(&mut output, &from, &to, &changes, &side_by_side).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "# one\na                A\n# two\nB                b\n\
+                    \x20                # three\n");
+    }
+
+    #[test]
+    fn test_diff_lines_every_format() {
+        // scripts with changes left out can be written in any format
+        let lines = ["a", "A", "", "# x", "# Y", "b"];
+        let comment = regex::Regex::new("^#").unwrap();
+        let mut random = Lcg(77);
+        for _ in 0..300 {
+            let mut lines_of = |len| -> Vec<_> {
+                random.sequence(len, 6).iter().map(|&n| lines[n as usize]).collect()
+            };
+            let (from, to) = (lines_of(10), lines_of(10));
+            let context = random.below(3) as usize;
+            let options = DiffOptions {
+                ignore_case: random.below(2) == 0,
+                ignore_blank_lines: random.below(2) == 0,
+                ignore_matching_lines: vec![comment.clone()],
+                context,
+                ..Default::default()
+            };
+            let changes = diff_lines(&from, &to, &options);
+
+            let mut output = Vec::new();
+            write_unified(&mut output, &from, &to, &changes, context).unwrap();
+            write_context(&mut output, &from, &to, &changes, context).unwrap();
+            write_word_diff(&mut output, &from, &to, &changes, context, WordStyle::Markup)
+                .unwrap();
+            write_ed_script(&mut output, &changes).unwrap();
+            write_rcs(&mut output, &changes).unwrap();
+            write_json(&mut output, "a", "b", &changes).unwrap();
+            let side_by_side = SideBySideOptions::default();
+            write_side_by_side(&mut output, &from, &to, &changes, &side_by_side).unwrap();
+            let marked = diff_lines_marked(&from, &to, &options);
+            write_side_by_side_marked(&mut output, &from, &to, &marked, &side_by_side).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_diff_without_display() {
+        // bytes and other items that can't be printed
+        let from = b"the quick fox".to_vec();
+        let to = b"the slow fox".to_vec();
+        let changes = diff(&from, &to);
+        assert_eq!(patch_all(&from, &changes), Ok(to.clone()));
+        assert_eq!(diff_with(&from, &to, Algorithm::Histogram).len(), changes.len());
+
+        // items without even PartialEq or Debug can be diffed by key
+        struct Opaque(u32);
+        let from = vec![Opaque(1), Opaque(2), Opaque(3)];
+        let to = vec![Opaque(1), Opaque(3)];
+        let changes = diff_by_key(&from, &to, |item| item.0);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].from_range(), 1..2);
+        assert_eq!(diff_by(&from, &to, |a, b| a.0 == b.0)[0].removed()[0].0, 2);
+    }
+
+    #[test]
+    fn test_owned_diff_item() {
+        let owned: Vec<OwnedDiffItem<String>> = {
+            let from: Vec<String> = "a b c d".split(' ').map(String::from).collect();
+            let to: Vec<String> = "x a c y".split(' ').map(String::from).collect();
+            let changes = diff(&from, &to);
+            let owned: Vec<_> = changes.iter().map(OwnedDiffItem::from).collect();
+            for (item, owned) in changes.iter().zip(&owned) {
+                assert_eq!(owned.as_diff_item(), *item);
+                assert_eq!(owned.to_string(), item.to_string());
+            }
+            owned
+        };
+
+        // the inputs are gone, but the edits still apply
+        let borrowed: Vec<_> = owned.iter().map(OwnedDiffItem::as_diff_item).collect();
+        let input: Vec<String> = "a b c d".split(' ').map(String::from).collect();
+        assert_eq!(patch_all(&input, &borrowed).unwrap(), vec!["x", "a", "c", "y"]);
+
+        let handle = std::thread::spawn(move || owned.len());
+        assert_eq!(handle.join().unwrap(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_owned_diff_item_serde() {
+        extern crate serde_json;
+
+        let from = vec!["a", "b", "c"];
+        let to = vec!["a", "B", "c", "d"];
+        let owned: Vec<OwnedDiffItem<&str>> = diff(&from, &to).iter().map(Into::into).collect();
+        let json = serde_json::to_string(&owned).unwrap();
+        let change = r#"{"Change":{"start_from":2,"end_from":2,"start_to":2,"end_to":2,"#;
+        let add = r#"{"Add":{"start_from":3,"start_to":4,"end_to":5,"items":["d"]}}"#;
+        assert_eq!(json, format!(r#"[{}"from":["b"],"to":["B"]}}}},{}]"#, change, add));
+
+        let loaded: Vec<OwnedDiffItem<String>> = serde_json::from_str(&json).unwrap();
+        let loaded: Vec<_> = loaded.iter().map(OwnedDiffItem::as_diff_item).collect();
+        let input: Vec<String> = from.iter().map(|line| line.to_string()).collect();
+        assert_eq!(patch_all(&input, &loaded).unwrap(), to);
+    }
+
+    #[test]
+    fn test_write_json() {
+        extern crate serde_json;
+
+        let mut output = Vec::new();
+        write_json(&mut output, "a", "b", &diff(&["same"], &["same"])).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "{\"files\":{\"from\":\"a\",\"to\":\"b\"},\
+                    \"summary\":{\"changes\":0,\"added\":0,\"deleted\":0},\"changes\":[]}\n");
+
+        let from = vec!["keep", "say \"hi\"\\", "tab\there", "bell\u{7}", "naïve ✓"];
+        let to = vec!["new", "keep", "naïve ✓"];
+        let changes = diff(&from, &to);
+        let mut output = Vec::new();
+        write_json(&mut output, "dir/\"old\"", "new", &changes).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains(r#""bell\u0007""#));
+
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["files"]["from"], "dir/\"old\"");
+        assert_eq!(json["summary"]["changes"], 2);
+        assert_eq!(json["summary"]["added"], 1);
+        assert_eq!(json["summary"]["deleted"], 3);
+        let add = &json["changes"][0];
+        assert_eq!(add["kind"], "add");
+        assert_eq!((&add["from"]["start"], &add["from"]["end"]), (&1.into(), &0.into()));
+        assert_eq!(add["to"]["lines"], serde_json::json!(["new"]));
+        let delete = &json["changes"][1];
+        assert_eq!(delete["kind"], "delete");
+        assert_eq!((&delete["from"]["start"], &delete["from"]["end"]), (&2.into(), &4.into()));
+        assert_eq!(delete["from"]["lines"], serde_json::json!(&from[1..4]));
+        assert_eq!((&delete["to"]["start"], &delete["to"]["end"]), (&3.into(), &2.into()));
+    }
 }
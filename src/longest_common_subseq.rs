@@ -13,8 +13,8 @@ pub fn build_lcs_table<'a, T: PartialEq>(from: &'a [T], to: &'a [T]) -> LCSTable
         }
     }
 
-    for i in 1..from.len() {
-        for j in 1..to.len() {
+    for i in 1..from.len() + 1 {
+        for j in 1..to.len() + 1 {
             if from[i - 1] == to[j - 1] {
                 table[i][j] = table[i - 1][j - 1] + 1;
             } else {
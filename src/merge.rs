@@ -0,0 +1,247 @@
+use diff;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// A piece of a three-way merge, as returned by merge
+#[derive(Debug, PartialEq, Eq)]
+pub enum MergeChunk<'a, T: 'a> {
+    /// Lines neither side changed
+    Unchanged(&'a [T]),
+    /// Lines only "ours" changed
+    Ours {
+        /// The lines of the base
+        base: &'a [T],
+        /// What "ours" changed them to
+        ours: &'a [T],
+    },
+    /// Lines only "theirs" changed
+    Theirs {
+        /// The lines of the base
+        base: &'a [T],
+        /// What "theirs" changed them to
+        theirs: &'a [T],
+    },
+    /// Lines both sides changed the same way
+    Both {
+        /// The lines of the base
+        base: &'a [T],
+        /// What both sides changed them to
+        merged: &'a [T],
+    },
+    /// Lines the sides changed differently, which need to be merged by hand
+    Conflict {
+        /// The lines of the base
+        base: &'a [T],
+        /// What "ours" changed them to
+        ours: &'a [T],
+        /// What "theirs" changed them to
+        theirs: &'a [T],
+    },
+}
+
+impl<'a, T: 'a> MergeChunk<'a, T> {
+    /// Whether this chunk is a conflict
+    pub fn is_conflict(&self) -> bool {
+        matches!(*self, MergeChunk::Conflict { .. })
+    }
+
+    /// The lines of the merge result, or None for a conflict
+    pub fn resolved(&self) -> Option<&'a [T]> {
+        match *self {
+            MergeChunk::Unchanged(lines) |
+            MergeChunk::Ours { ours: lines, .. } |
+            MergeChunk::Theirs { theirs: lines, .. } |
+            MergeChunk::Both { merged: lines, .. } => Some(lines),
+            MergeChunk::Conflict { .. } => None,
+        }
+    }
+}
+
+/// The names shown in the conflict markers written by write_merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeLabels<'l> {
+    /// Shown after "<<<<<<<"
+    pub ours: &'l str,
+    /// Shown after "|||||||"
+    pub base: &'l str,
+    /// Shown after ">>>>>>>"
+    pub theirs: &'l str,
+}
+
+/// Merges the changes "ours" and "theirs" each made to "base".
+///
+/// Both are diffed against the base with diff(). Changes to different
+/// parts of the base are both kept. Changes that overlap, or that touch
+/// without any unchanged line between them, are a conflict unless they are
+/// the same.
+///
+/// # Example
+/// ```
+/// use diff::{merge, MergeChunk};
+///
+/// let base = vec!["a", "b", "c", "d"];
+/// let ours = vec!["a", "B", "c", "d"];
+/// let theirs = vec!["a", "b", "c", "D"];
+///
+/// let chunks = merge(&base, &ours, &theirs);
+/// assert_eq!(chunks[1], MergeChunk::Ours { base: &base[1..2], ours: &ours[1..2] });
+/// assert_eq!(chunks[3], MergeChunk::Theirs { base: &base[3..], theirs: &theirs[3..] });
+/// assert!(!chunks.iter().any(MergeChunk::is_conflict));
+/// ```
+pub fn merge<'a, T: PartialEq>(base: &'a [T], ours: &'a [T], theirs: &'a [T])
+                               -> Vec<MergeChunk<'a, T>> {
+    let ours_changes = changes(base, ours);
+    let theirs_changes = changes(base, theirs);
+
+    let mut chunks = Vec::new();
+    let mut line = 0;
+    let (mut next_ours, mut next_theirs) = (0, 0);
+    loop {
+        // the earliest change left starts the next group
+        let start = match (ours_changes.get(next_ours), theirs_changes.get(next_theirs)) {
+            (Some(ours), Some(theirs)) => ours.base.start.min(theirs.base.start),
+            (Some(change), None) | (None, Some(change)) => change.base.start,
+            (None, None) => break,
+        };
+        if line < start {
+            chunks.push(MergeChunk::Unchanged(&base[line..start]));
+        }
+
+        // take changes from either side until there's a gap after them
+        let (first_ours, first_theirs) = (next_ours, next_theirs);
+        let mut end = start;
+        loop {
+            if ours_changes.get(next_ours).is_some_and(|change| change.base.start <= end) {
+                end = end.max(ours_changes[next_ours].base.end);
+                next_ours += 1;
+            } else if theirs_changes.get(next_theirs)
+                .is_some_and(|change| change.base.start <= end) {
+                end = end.max(theirs_changes[next_theirs].base.end);
+                next_theirs += 1;
+            } else {
+                break;
+            }
+        }
+
+        let base_lines = &base[start..end];
+        let ours_range = side_range(&ours_changes, first_ours, next_ours, start, end);
+        let theirs_range = side_range(&theirs_changes, first_theirs, next_theirs, start, end);
+        let (ours_lines, theirs_lines) = (&ours[ours_range], &theirs[theirs_range]);
+        chunks.push(if first_theirs == next_theirs {
+            MergeChunk::Ours {
+                base: base_lines,
+                ours: ours_lines,
+            }
+        } else if first_ours == next_ours {
+            MergeChunk::Theirs {
+                base: base_lines,
+                theirs: theirs_lines,
+            }
+        } else if ours_lines == theirs_lines {
+            MergeChunk::Both {
+                base: base_lines,
+                merged: ours_lines,
+            }
+        } else {
+            MergeChunk::Conflict {
+                base: base_lines,
+                ours: ours_lines,
+                theirs: theirs_lines,
+            }
+        });
+        line = end;
+    }
+    if line < base.len() {
+        chunks.push(MergeChunk::Unchanged(&base[line..]));
+    }
+    chunks
+}
+
+/// Writes the result of a merge, with conflicts shown between markers as
+/// diff3 -m does:
+///
+/// ```text
+/// <<<<<<< ours
+/// our lines
+/// ||||||| base
+/// the lines of the base
+/// =======
+/// their lines
+/// >>>>>>> theirs
+/// ```
+///
+/// # Example
+/// ```
+/// use diff::{merge, write_merge, MergeLabels};
+///
+/// let base = vec!["a", "b", "c"];
+/// let ours = vec!["a", "x", "c"];
+/// let theirs = vec!["a", "y", "c"];
+///
+/// let labels = MergeLabels { ours: "ours", base: "base", theirs: "theirs" };
+/// let mut output = Vec::new();
+/// write_merge(&mut output, &merge(&base, &ours, &theirs), &labels).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "a\n<<<<<<< ours\nx\n||||||| base\nb\n=======\ny\n>>>>>>> theirs\nc\n");
+/// ```
+pub fn write_merge<'a, T, W>(mut writer: W,
+                             chunks: &[MergeChunk<'a, T>],
+                             labels: &MergeLabels)
+                             -> io::Result<()>
+    where T: Display,
+          W: Write
+{
+    for chunk in chunks {
+        match *chunk {
+            MergeChunk::Conflict { base, ours, theirs } => {
+                writeln!(writer, "<<<<<<< {}", labels.ours)?;
+                write_lines(&mut writer, ours)?;
+                writeln!(writer, "||||||| {}", labels.base)?;
+                write_lines(&mut writer, base)?;
+                writeln!(writer, "=======")?;
+                write_lines(&mut writer, theirs)?;
+                writeln!(writer, ">>>>>>> {}", labels.theirs)?;
+            }
+            _ => write_lines(&mut writer, chunk.resolved().unwrap_or(&[]))?,
+        }
+    }
+    Ok(())
+}
+
+fn write_lines<T: Display, W: Write>(writer: &mut W, lines: &[T]) -> io::Result<()> {
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// A change one side made to the base, as 0-based ranges
+struct Change {
+    base: Range<usize>,
+    side: Range<usize>,
+}
+
+/// The changes from base to side
+fn changes<T: PartialEq>(base: &[T], side: &[T]) -> Vec<Change> {
+    diff(base, side)
+        .iter()
+        .map(|item| {
+            Change {
+                base: item.from_range(),
+                side: item.to_range(),
+            }
+        })
+        .collect()
+}
+
+/// The lines of one side matching base[start..end], given that side's
+/// changes[first..next] are the ones within it
+fn side_range(changes: &[Change], first: usize, next: usize, start: usize, end: usize)
+              -> Range<usize> {
+    // outside of changes, a side is the base shifted by the changes before
+    let shift = |change: &Change| change.side.end as isize - change.base.end as isize;
+    let before = if first == 0 { 0 } else { shift(&changes[first - 1]) };
+    let after = if next == 0 { 0 } else { shift(&changes[next - 1]) };
+    (start as isize + before) as usize..(end as isize + after) as usize
+}
@@ -0,0 +1,251 @@
+use diffhelpers::EditFlags;
+use preprocess::{common_prefix_len, common_suffix_len};
+use std::ops::{Index, IndexMut, Range};
+
+/// Find all the edits necessary to make "from" match "to" using Myers'
+/// greedy O((N+M)D) algorithm, where D is the length of the edit script.
+///
+/// Rather than a full (N+1)x(M+1) table, only the furthest reaching point
+/// of every diagonal is kept for each value of D, so memory grows with the
+/// number of differences instead of the size of the inputs.
+/// Produces the same kind of Vec<EditFlags> as make_diffs.
+pub fn myers_diffs<T: PartialEq>(from: &[T], to: &[T]) -> Vec<EditFlags> {
+    let n = from.len();
+    let m = to.len();
+    let max = n + m;
+    let offset = max + 1;
+    let mut v = vec![0usize; 2 * max + 3];
+    // trace[d] holds v[-d..d] as it was before the d-th round of edits
+    let mut trace: Vec<Vec<usize>> = vec![];
+
+    'rounds: for d in 0..max + 1 {
+        trace.push(v[offset - d..offset + d + 1].to_vec());
+        let d = d as isize;
+        let mut k = -d;
+        while k <= d {
+            let idx = (offset as isize + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            while x < n && y < m && from[x] == to[y] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'rounds;
+            }
+            k += 2;
+        }
+    }
+
+    backtrack(&trace, n, m)
+}
+
+/// Helper function for myers_diffs
+/// Walks back through the saved rounds from the end of both sequences,
+/// emitting one edit per round and a Same for every step along a snake.
+fn backtrack(trace: &[Vec<usize>], n: usize, m: usize) -> Vec<EditFlags> {
+    let mut diffs = Vec::with_capacity(n + m);
+    let mut x = n;
+    let mut y = m;
+
+    for (d, v) in trace.iter().enumerate().skip(1).rev() {
+        let d = d as isize;
+        let k = x as isize - y as isize;
+        // v is indexed from -d, so diagonal k lives at k + d
+        let at = |k: isize| v[(k + d) as usize];
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = (prev_x as isize - prev_k) as usize;
+
+        while x > prev_x && y > prev_y {
+            diffs.push(EditFlags::Same);
+            x -= 1;
+            y -= 1;
+        }
+        if x == prev_x {
+            diffs.push(EditFlags::Add);
+        } else {
+            diffs.push(EditFlags::Delete);
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    // whatever is left is the snake followed before the first edit
+    while x > 0 && y > 0 {
+        diffs.push(EditFlags::Same);
+        x -= 1;
+        y -= 1;
+    }
+
+    diffs.reverse();
+    diffs
+}
+
+/// Find all the edits necessary to make "from" match "to" using the linear
+/// space refinement of Myers' algorithm.
+///
+/// Instead of keeping every round for backtracking, the middle snake of the
+/// shortest edit script is found by running the greedy search forwards and
+/// backwards at the same time. The inputs are split around it and each half
+/// is solved the same way, so only two V arrays of size N+M are ever needed.
+/// Produces the same kind of Vec<EditFlags> as make_diffs.
+pub fn linear_myers_diffs<T: PartialEq>(from: &[T], to: &[T]) -> Vec<EditFlags> {
+    let max_d = (from.len() + to.len()).div_ceil(2) + 1;
+    let mut vf = V::new(max_d);
+    let mut vb = V::new(max_d);
+    let mut diffs = Vec::with_capacity(from.len() + to.len());
+    conquer(from,
+            0..from.len(),
+            to,
+            0..to.len(),
+            &mut vf,
+            &mut vb,
+            &mut diffs);
+    diffs
+}
+
+/// A V array from Myers' paper: the furthest x reached on each diagonal k,
+/// where k can be negative
+struct V {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl V {
+    fn new(max_d: usize) -> V {
+        V {
+            offset: max_d as isize,
+            v: vec![0; 2 * max_d + 1],
+        }
+    }
+}
+
+impl Index<isize> for V {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl IndexMut<isize> for V {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
+
+/// Helper function for linear_myers_diffs
+/// Emits the edits for from[from_range] -> to[to_range] by splitting both
+/// ranges around their middle snake and recursing into each half.
+fn conquer<T: PartialEq>(from: &[T],
+                         mut from_range: Range<usize>,
+                         to: &[T],
+                         mut to_range: Range<usize>,
+                         vf: &mut V,
+                         vb: &mut V,
+                         diffs: &mut Vec<EditFlags>) {
+    let prefix = common_prefix_len(&from[from_range.clone()], &to[to_range.clone()]);
+    for _ in 0..prefix {
+        diffs.push(EditFlags::Same);
+    }
+    from_range.start += prefix;
+    to_range.start += prefix;
+
+    let suffix = common_suffix_len(&from[from_range.clone()], &to[to_range.clone()]);
+    from_range.end -= suffix;
+    to_range.end -= suffix;
+
+    if from_range.start == from_range.end {
+        for _ in to_range {
+            diffs.push(EditFlags::Add);
+        }
+    } else if to_range.start == to_range.end {
+        for _ in from_range {
+            diffs.push(EditFlags::Delete);
+        }
+    } else {
+        let (x, y) = find_middle_snake(from, from_range.clone(), to, to_range.clone(), vf, vb);
+        conquer(from, from_range.start..x, to, to_range.start..y, vf, vb, diffs);
+        conquer(from, x..from_range.end, to, y..to_range.end, vf, vb, diffs);
+    }
+
+    for _ in 0..suffix {
+        diffs.push(EditFlags::Same);
+    }
+}
+
+/// Helper function for conquer
+/// Returns a point on the middle snake of the shortest edit script between
+/// two non-empty ranges, in the coordinates of the full slices.
+fn find_middle_snake<T: PartialEq>(from: &[T],
+                                   from_range: Range<usize>,
+                                   to: &[T],
+                                   to_range: Range<usize>,
+                                   vf: &mut V,
+                                   vb: &mut V)
+                                   -> (usize, usize) {
+    let from = &from[from_range.clone()];
+    let to = &to[to_range.clone()];
+    let n = from.len();
+    let m = to.len();
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+    vf[1] = 0;
+    vb[1] = 0;
+
+    let max_d = ((n + m).div_ceil(2) + 1) as isize;
+    for d in 0..max_d {
+        // extend the forward paths from the top left corner
+        let mut k = d;
+        while k >= -d {
+            let mut x = if k == -d || (k != d && vf[k - 1] < vf[k + 1]) {
+                vf[k + 1]
+            } else {
+                vf[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let (x0, y0) = (x, y);
+            if x < n && y < m {
+                x += common_prefix_len(&from[x..], &to[y..]);
+            }
+            vf[k] = x;
+            if odd && (k - delta).abs() < d && vf[k] + vb[-(k - delta)] >= n {
+                return (x0 + from_range.start, y0 + to_range.start);
+            }
+            k -= 2;
+        }
+
+        // extend the backward paths from the bottom right corner
+        let mut k = d;
+        while k >= -d {
+            let mut x = if k == -d || (k != d && vb[k - 1] < vb[k + 1]) {
+                vb[k + 1]
+            } else {
+                vb[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            if x < n && y < m {
+                let snake = common_suffix_len(&from[..n - x], &to[..m - y]);
+                x += snake;
+                y += snake;
+            }
+            vb[k] = x;
+            if !odd && (k - delta).abs() <= d && vb[k] + vf[-(k - delta)] >= n {
+                return (n - x + from_range.start, m - y + to_range.start);
+            }
+            k -= 2;
+        }
+    }
+
+    unreachable!("the forward and backward searches always overlap")
+}
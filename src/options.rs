@@ -0,0 +1,290 @@
+use algorithm::Algorithm;
+use diffhelpers::convert_to_diffitems;
+use diffitem::DiffItem;
+use hunks::{group, EditScript};
+use key_diffs;
+use regex::Regex;
+use std::borrow::Cow;
+use std::ops::Deref;
+use std::slice;
+
+/// How whitespace is treated when lines are compared by diff_lines. Each
+/// variant ignores everything the ones before it do, so the greater of two
+/// settings is the one that ignores more.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Whitespace {
+    /// Lines must match exactly
+    #[default]
+    Exact,
+    /// Whitespace at the end of a line is ignored (--ignore-trailing-space)
+    IgnoreTrailing,
+    /// Trailing whitespace is ignored, and any run of whitespace matches any
+    /// other (-b, --ignore-space-change)
+    IgnoreAmount,
+    /// All whitespace is ignored, even where the other line has none (-w,
+    /// --ignore-all-space)
+    IgnoreAll,
+}
+
+impl Whitespace {
+    /// The form of a line that is compared: two lines match if their
+    /// normalized forms are equal
+    ///
+    /// # Example
+    /// ```
+    /// use diff::Whitespace;
+    ///
+    /// assert_eq!(Whitespace::IgnoreAmount.normalize("  a \t b  "), " a b");
+    /// assert_eq!(Whitespace::IgnoreAll.normalize("  a \t b  "), "ab");
+    /// ```
+    pub fn normalize<'l>(&self, line: &'l str) -> Cow<'l, str> {
+        match *self {
+            Whitespace::Exact => Cow::Borrowed(line),
+            Whitespace::IgnoreTrailing => Cow::Borrowed(line.trim_end()),
+            Whitespace::IgnoreAmount => {
+                let mut normalized = String::with_capacity(line.len());
+                let mut in_space = false;
+                for c in line.trim_end().chars() {
+                    if c.is_whitespace() {
+                        if !in_space {
+                            normalized.push(' ');
+                        }
+                    } else {
+                        normalized.push(c);
+                    }
+                    in_space = c.is_whitespace();
+                }
+                Cow::Owned(normalized)
+            }
+            Whitespace::IgnoreAll => {
+                Cow::Owned(line.chars().filter(|c| !c.is_whitespace()).collect())
+            }
+        }
+    }
+}
+
+/// Whether the last line of each file has no newline after it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MissingNewline {
+    /// The last line of "from" has no newline
+    pub from: bool,
+    /// The last line of "to" has no newline
+    pub to: bool,
+}
+
+/// Settings for diff_lines
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// How whitespace is compared
+    pub whitespace: Whitespace,
+    /// Compare lines without regard to case (-i, --ignore-case)
+    pub ignore_case: bool,
+    /// Leave out changes that only add or remove blank lines (-B,
+    /// --ignore-blank-lines). A line counts as blank if nothing is left of
+    /// it once whitespace is treated as the whitespace setting says.
+    pub ignore_blank_lines: bool,
+    /// Leave out changes whose lines all match one of these patterns (-I,
+    /// --ignore-matching-lines). A pattern matches if it is found anywhere
+    /// in the original line.
+    pub ignore_matching_lines: Vec<Regex>,
+    /// The number of context lines the diff will be printed with. As in
+    /// GNU diff, a change that would be left out for ignore_blank_lines or
+    /// ignore_matching_lines is kept if it ends up in the same hunk as one
+    /// that isn't. Leave it at 0 for formats without context.
+    pub context: usize,
+    /// The algorithm to use, or None to choose one the way diff does
+    pub algorithm: Option<Algorithm>,
+    /// Which files end without a newline. As in GNU diff, a last line with
+    /// no newline is different from the same line with one.
+    pub missing_newline: MissingNewline,
+}
+
+impl DiffOptions {
+    /// The form of a line that is compared, with whitespace and case
+    /// treated as these options say
+    ///
+    /// # Example
+    /// ```
+    /// use diff::{DiffOptions, Whitespace};
+    ///
+    /// let options = DiffOptions {
+    ///     whitespace: Whitespace::IgnoreAll,
+    ///     ignore_case: true,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(options.normalize("Hello, World "), "hello,world");
+    /// ```
+    pub fn normalize<'l>(&self, line: &'l str) -> Cow<'l, str> {
+        let normalized = self.whitespace.normalize(line);
+        if self.ignore_case {
+            Cow::Owned(normalized.to_lowercase())
+        } else {
+            normalized
+        }
+    }
+
+    /// Whether a line may be changed without the change being shown, given
+    /// its normalized form
+    fn is_ignorable(&self, line: &str, normalized: &str) -> bool {
+        (self.ignore_blank_lines && normalized.is_empty()) ||
+        self.ignore_matching_lines.iter().any(|pattern| pattern.is_match(line))
+    }
+}
+
+/// The edit script diff_lines returns. It derefs to a slice of DiffItems,
+/// and also remembers which of them are only kept because they share a hunk
+/// with another change, so unified, context and word diff output group them
+/// into the same hunks diff_lines did.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LineDiff<'a, T: 'a> {
+    /// The changes shown
+    pub items: Vec<DiffItem<'a, T>>,
+    /// Whether each change only changes lines ignore_blank_lines or
+    /// ignore_matching_lines would leave out
+    pub ignorable: Vec<bool>,
+}
+
+impl<'a, T> Deref for LineDiff<'a, T> {
+    type Target = [DiffItem<'a, T>];
+
+    fn deref(&self) -> &[DiffItem<'a, T>] {
+        &self.items
+    }
+}
+
+impl<'d, 'a, T> IntoIterator for &'d LineDiff<'a, T> {
+    type Item = &'d DiffItem<'a, T>;
+    type IntoIter = slice::Iter<'d, DiffItem<'a, T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<'a, T> EditScript<'a, T> for LineDiff<'a, T> {
+    fn items(&self) -> &[DiffItem<'a, T>] {
+        &self.items
+    }
+
+    fn is_ignorable(&self, index: usize) -> bool {
+        self.ignorable[index]
+    }
+}
+
+/// Calculate a diff of lines of text, comparing them as options say.
+///
+/// Lines that only match once normalized are treated as unchanged, but the
+/// DiffItems still hold the original lines, so printing or patching with
+/// them uses the text as it was. Changes left out for ignore_blank_lines or
+/// ignore_matching_lines are simply missing from the result, which every
+/// output format, side by side included, allows for. Pass the result to
+/// write_unified, write_context or write_word_diff with the same context
+/// as options, so they group the changes into the hunks diff_lines chose.
+///
+/// # Example
+/// ```
+/// use diff::{diff_lines, DiffOptions, Whitespace};
+///
+/// let from = vec!["fn main() {", "  run();", "}"];
+/// let to = vec!["fn main() {", "    run();  ", "", "}"];
+///
+/// let options = DiffOptions {
+///     whitespace: Whitespace::IgnoreAmount,
+///     ignore_blank_lines: true,
+///     ..Default::default()
+/// };
+/// assert!(diff_lines(&from, &to, &options).is_empty());
+///
+/// // the reindented line still counts as changed by default
+/// assert_eq!(diff_lines(&from, &to, &DiffOptions::default()).len(), 1);
+/// ```
+pub fn diff_lines<'a, T>(from: &'a [T], to: &'a [T], options: &DiffOptions) -> LineDiff<'a, T>
+    where T: AsRef<str>
+{
+    let (items, ignorable) = classify(from, to, options)
+        .into_iter()
+        .filter(|&(_, _, shown)| shown)
+        .map(|(item, ignored, _)| (item, ignored))
+        .unzip();
+    LineDiff { items, ignorable }
+}
+
+/// Calculate a diff of lines of text as diff_lines does, but keep the
+/// changes it would leave out for ignore_blank_lines and
+/// ignore_matching_lines. Each item is paired with whether diff_lines
+/// would show it, so the script still accounts for every difference, as
+/// output that shows both files in full needs.
+///
+/// # Example
+/// ```
+/// use diff::{diff_lines_marked, DiffOptions};
+///
+/// let from = vec!["a", "", "b"];
+/// let to = vec!["a", "b", "c"];
+///
+/// let options = DiffOptions { ignore_blank_lines: true, ..Default::default() };
+/// let marked = diff_lines_marked(&from, &to, &options);
+/// assert_eq!(marked.len(), 2);
+/// assert_eq!(marked[0].0.removed(), &[""]);
+/// assert!(!marked[0].1);
+/// assert!(marked[1].1);
+/// ```
+pub fn diff_lines_marked<'a, T>(from: &'a [T],
+                                to: &'a [T],
+                                options: &DiffOptions)
+                                -> Vec<(DiffItem<'a, T>, bool)>
+    where T: AsRef<str>
+{
+    classify(from, to, options).into_iter().map(|(item, _, shown)| (item, shown)).collect()
+}
+
+/// Calculate a diff of lines of text, pairing each item with whether it is
+/// ignorable and whether diff_lines shows it
+fn classify<'a, T>(from: &'a [T],
+                   to: &'a [T],
+                   options: &DiffOptions)
+                   -> Vec<(DiffItem<'a, T>, bool, bool)>
+    where T: AsRef<str>
+{
+    let normalize = |lines: &'a [T]| -> Vec<Cow<'a, str>> {
+        lines.iter().map(|line| options.normalize(line.as_ref())).collect()
+    };
+    let (mut from_keys, mut to_keys) = (normalize(from), normalize(to));
+    mark_missing_newline(&mut from_keys, options.missing_newline.from);
+    mark_missing_newline(&mut to_keys, options.missing_newline.to);
+    let diffs = key_diffs(&from_keys, &to_keys, options.algorithm);
+    let items = convert_to_diffitems(from, to, &diffs);
+    if !options.ignore_blank_lines && options.ignore_matching_lines.is_empty() {
+        return items.into_iter().map(|item| (item, false, true)).collect();
+    }
+    let ignorable = |lines: &[T]| {
+        lines.iter().all(|line| {
+            let line = line.as_ref();
+            options.is_ignorable(line, &options.normalize(line))
+        })
+    };
+    let ignored: Vec<bool> = items.iter()
+        .map(|item| ignorable(&from[item.from_range()]) && ignorable(&to[item.to_range()]))
+        .collect();
+
+    // hide the hunks that only have ignored changes
+    let mut shown = vec![false; items.len()];
+    for range in group(&items, |index| ignored[index], options.context) {
+        if ignored[range.clone()].contains(&false) {
+            shown[range].iter_mut().for_each(|shown| *shown = true);
+        }
+    }
+    items.into_iter()
+        .zip(ignored)
+        .zip(shown)
+        .map(|((item, ignored), shown)| (item, ignored, shown))
+        .collect()
+}
+
+/// Makes the last key of a file with no newline at the end differ from the
+/// same line with one. Lines never hold a newline, so it is used as the mark.
+fn mark_missing_newline(keys: &mut [Cow<str>], missing_newline: bool) {
+    if let (Some(last), true) = (keys.last_mut(), missing_newline) {
+        last.to_mut().push('\n');
+    }
+}
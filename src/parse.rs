@@ -0,0 +1,492 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+use unified::unified_range;
+
+/// One line of a parsed hunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchLine {
+    /// A line found in both files (" " in a unified diff)
+    Context(String),
+    /// A line only in the original file ("-" or "<")
+    Removed(String),
+    /// A line only in the new file ("+" or ">")
+    Added(String),
+}
+
+/// A parsed hunk: a run of changed lines and the context around them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchHunk {
+    /// The lines of the original file covered by the hunk, 0-based. An empty
+    /// range is the position of an insertion.
+    pub from: Range<usize>,
+    /// The lines of the new file covered by the hunk, 0-based
+    pub to: Range<usize>,
+    /// The lines of the hunk in order
+    pub lines: Vec<PatchLine>,
+    /// The last line of the original file has no trailing newline
+    pub from_missing_newline: bool,
+    /// The last line of the new file has no trailing newline
+    pub to_missing_newline: bool,
+}
+
+impl PatchHunk {
+    /// The lines the hunk expects to find in the original file: its context
+    /// and removed lines
+    pub fn from_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match *line {
+                PatchLine::Context(ref text) |
+                PatchLine::Removed(ref text) => Some(text.as_str()),
+                PatchLine::Added(_) => None,
+            })
+            .collect()
+    }
+
+    /// The lines the hunk leaves in the new file: its context and added lines
+    pub fn to_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match *line {
+                PatchLine::Context(ref text) |
+                PatchLine::Added(ref text) => Some(text.as_str()),
+                PatchLine::Removed(_) => None,
+            })
+            .collect()
+    }
+
+    /// The same hunk in the other direction, turning the new file back into
+    /// the original: added and removed lines trade places
+    ///
+    /// # Example
+    /// ```
+    /// use diff::{parse_unified, PatchLine};
+    ///
+    /// let files = parse_unified("--- a\n+++ b\n@@ -1 +1,2 @@\n x\n+y\n").unwrap();
+    /// let inverted = files[0].hunks[0].invert();
+    /// assert_eq!(inverted.from, 0..2);
+    /// assert_eq!(inverted.lines[1], PatchLine::Removed("y".to_string()));
+    /// ```
+    pub fn invert(&self) -> PatchHunk {
+        PatchHunk {
+            from: self.to.clone(),
+            to: self.from.clone(),
+            lines: self.lines
+                .iter()
+                .map(|line| match *line {
+                    PatchLine::Context(ref text) => PatchLine::Context(text.clone()),
+                    PatchLine::Removed(ref text) => PatchLine::Added(text.clone()),
+                    PatchLine::Added(ref text) => PatchLine::Removed(text.clone()),
+                })
+                .collect(),
+            from_missing_newline: self.to_missing_newline,
+            to_missing_newline: self.from_missing_newline,
+        }
+    }
+}
+
+impl Display for PatchHunk {
+    /// Formats the hunk as in a unified diff, starting with its "@@" line
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "@@ -{} +{} @@", unified_range(&self.from), unified_range(&self.to))?;
+        let last_from = self.lines.iter().rposition(|line| !matches!(*line, PatchLine::Added(_)));
+        let last_to = self.lines.iter().rposition(|line| !matches!(*line, PatchLine::Removed(_)));
+        for (n, line) in self.lines.iter().enumerate() {
+            match *line {
+                PatchLine::Context(ref text) => writeln!(f, " {}", text)?,
+                PatchLine::Removed(ref text) => writeln!(f, "-{}", text)?,
+                PatchLine::Added(ref text) => writeln!(f, "+{}", text)?,
+            }
+            if self.from_missing_newline && last_from == Some(n) ||
+               self.to_missing_newline && last_to == Some(n) {
+                writeln!(f, "\\ No newline at end of file")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The hunks for one file of a unified diff, with the paths from its
+/// "---" and "+++" lines (timestamps are dropped)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// The path of the original file
+    pub from_path: String,
+    /// The path of the new file
+    pub to_path: String,
+    /// The hunks in order
+    pub hunks: Vec<PatchHunk>,
+}
+
+/// Where and why patch text couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line of the input
+    pub line: usize,
+    /// The 1-based column (in characters) of the line
+    pub column: usize,
+    /// What was wrong there
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, column: usize, message: &str) -> ParseError {
+        ParseError {
+            line: line + 1,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a unified diff, as written by diff -u, git diff, or
+/// write_unified, into one FilePatch per file.
+///
+/// Lines outside of files and hunks, like git's "diff --git" and "index"
+/// lines, are skipped. Every hunk must have as many lines as its "@@" line
+/// says.
+///
+/// # Example
+/// ```
+/// use diff::{parse_unified, PatchLine};
+///
+/// let text = "--- a.txt\n+++ b.txt\n@@ -2,3 +2,3 @@\n is\n-an\n+another\n example\n";
+/// let files = parse_unified(text).unwrap();
+/// assert_eq!(files[0].to_path, "b.txt");
+/// assert_eq!(files[0].hunks[0].from, 1..4);
+/// assert_eq!(files[0].hunks[0].lines[1], PatchLine::Removed("an".to_string()));
+/// ```
+pub fn parse_unified(text: &str) -> Result<Vec<FilePatch>, ParseError> {
+    let lines = split_lines(text);
+    let mut files = Vec::new();
+    let mut n = 0;
+    while n < lines.len() {
+        if lines[n].starts_with("@@ ") {
+            return Err(ParseError::new(n, 1, "hunk without a \"---\" and \"+++\" header"));
+        }
+        if !lines[n].starts_with("--- ") {
+            n += 1;
+            continue;
+        }
+
+        let from_path = header_path(lines[n]);
+        n += 1;
+        match lines.get(n) {
+            Some(line) if line.starts_with("+++ ") => {}
+            _ => return Err(ParseError::new(n, 1, "expected a \"+++\" line")),
+        }
+        let to_path = header_path(lines[n]);
+        n += 1;
+
+        let mut hunks = Vec::new();
+        while n < lines.len() && lines[n].starts_with("@@ ") {
+            let (hunk, next) = unified_hunk(&lines, n)?;
+            hunks.push(hunk);
+            n = next;
+        }
+        files.push(FilePatch {
+            from_path,
+            to_path,
+            hunks,
+        });
+    }
+    Ok(files)
+}
+
+/// Parses a normal format diff, as written by diff or by printing
+/// DiffItems, into hunks without context.
+///
+/// # Example
+/// ```
+/// use diff::{diff, parse_normal, PatchLine};
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+///
+/// let text: String = diff(&from, &to).iter().map(|edit| edit.to_string()).collect();
+/// let hunks = parse_normal(&text).unwrap();
+/// assert_eq!(hunks[0].from, 2..3);
+/// assert_eq!(hunks[0].lines,
+///            vec![PatchLine::Removed("an".to_string()),
+///                 PatchLine::Added("another".to_string())]);
+/// ```
+pub fn parse_normal(text: &str) -> Result<Vec<PatchHunk>, ParseError> {
+    let lines = split_lines(text);
+    let mut hunks = Vec::new();
+    let mut n = 0;
+    while n < lines.len() {
+        let (hunk, next) = normal_hunk(&lines, n)?;
+        hunks.push(hunk);
+        n = next;
+    }
+    Ok(hunks)
+}
+
+/// The path from a "---" or "+++" line, without the timestamp after a tab
+fn header_path(line: &str) -> String {
+    let path = &line[4..];
+    path.split('\t').next().unwrap_or(path).to_string()
+}
+
+/// Parses the hunk whose "@@" line is lines[start], returning it and the
+/// index of the line after it
+fn unified_hunk(lines: &[&str], start: usize) -> Result<(PatchHunk, usize), ParseError> {
+    let mut header = Cursor::new(lines[start], start);
+    header.expect("@@ -")?;
+    let from = header.unified_range()?;
+    header.expect(" +")?;
+    let to = header.unified_range()?;
+    header.expect(" @@")?;
+
+    let mut hunk = PatchHunk {
+        from: from.clone(),
+        to: to.clone(),
+        lines: Vec::new(),
+        from_missing_newline: false,
+        to_missing_newline: false,
+    };
+    let (mut from_left, mut to_left) = (from.end - from.start, to.end - to.start);
+    let mut n = start + 1;
+    while from_left > 0 || to_left > 0 {
+        let line = match lines.get(n) {
+            Some(line) => *line,
+            None => return Err(ParseError::new(n, 1, "hunk ends early")),
+        };
+        let text = line.get(1..).unwrap_or("").to_string();
+        let (patch_line, from_used, to_used) = match line.chars().next() {
+            // some tools strip the space from empty context lines
+            Some(' ') | None => (PatchLine::Context(text), 1, 1),
+            Some('-') => (PatchLine::Removed(text), 1, 0),
+            Some('+') => (PatchLine::Added(text), 0, 1),
+            Some('\\') => {
+                missing_newline(&mut hunk, n)?;
+                n += 1;
+                continue;
+            }
+            Some(_) => return Err(ParseError::new(n, 1, "expected \" \", \"-\" or \"+\"")),
+        };
+        if from_used > from_left || to_used > to_left {
+            return Err(ParseError::new(n, 1, "more lines than the \"@@\" line says"));
+        }
+        from_left -= from_used;
+        to_left -= to_used;
+        hunk.lines.push(patch_line);
+        n += 1;
+    }
+    if lines.get(n).is_some_and(|line| line.starts_with('\\')) {
+        missing_newline(&mut hunk, n)?;
+        n += 1;
+    }
+    Ok((hunk, n))
+}
+
+/// Records a "\ No newline at end of file" line against the side(s) of
+/// the line before it
+fn missing_newline(hunk: &mut PatchHunk, n: usize) -> Result<(), ParseError> {
+    match hunk.lines.last() {
+        Some(&PatchLine::Context(_)) => {
+            hunk.from_missing_newline = true;
+            hunk.to_missing_newline = true;
+        }
+        Some(&PatchLine::Removed(_)) => hunk.from_missing_newline = true,
+        Some(&PatchLine::Added(_)) => hunk.to_missing_newline = true,
+        None => return Err(ParseError::new(n, 1, "\"\\\" line without a line before it")),
+    }
+    Ok(())
+}
+
+/// Splits text into lines on "\n" only, so lines of files with "\r\n" line
+/// endings keep their "\r"
+fn split_lines(text: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    if text.is_empty() || text.ends_with('\n') {
+        lines.pop();
+    }
+    lines
+}
+
+/// Parses the normal format command at lines[start] and its lines,
+/// returning the hunk and the index of the line after it
+fn normal_hunk(lines: &[&str], start: usize) -> Result<(PatchHunk, usize), ParseError> {
+    let mut command = Cursor::new(lines[start], start);
+    let (first, last) = command.normal_range()?;
+    let column = command.column();
+    let letter = command.next();
+    let (to_first, to_last) = command.normal_range()?;
+    command.end()?;
+
+    if first == 0 && letter != Some('a') || to_first == 0 && letter != Some('d') {
+        return Err(ParseError::new(start, 1, "line numbers start at 1"));
+    }
+    let (from, to) = match letter {
+        Some('a') if first == last => (first..first, to_first - 1..to_last),
+        Some('d') if to_first == to_last => (first - 1..last, to_first..to_first),
+        Some('c') => (first - 1..last, to_first - 1..to_last),
+        Some('a') | Some('d') => {
+            return Err(ParseError::new(start, 1, "expected a single line number for the \
+                                                  unchanged side"))
+        }
+        _ => return Err(ParseError::new(start, column, "expected \"a\", \"c\" or \"d\"")),
+    };
+
+    let mut hunk = PatchHunk {
+        from: from.clone(),
+        to: to.clone(),
+        lines: Vec::new(),
+        from_missing_newline: false,
+        to_missing_newline: false,
+    };
+    let mut n = start + 1;
+    n = normal_lines(lines, n, "< ", from.end - from.start, &mut hunk)?;
+    if letter == Some('c') {
+        if lines.get(n) != Some(&"---") {
+            return Err(ParseError::new(n, 1, "expected \"---\""));
+        }
+        n += 1;
+    }
+    n = normal_lines(lines, n, "> ", to.end - to.start, &mut hunk)?;
+    Ok((hunk, n))
+}
+
+/// Reads count lines starting with prefix ("< " or "> ") into a hunk,
+/// returning the index of the line after them
+fn normal_lines(lines: &[&str],
+                mut n: usize,
+                prefix: &str,
+                count: usize,
+                hunk: &mut PatchHunk)
+                -> Result<usize, ParseError> {
+    for _ in 0..count {
+        let text = match lines.get(n) {
+            Some(line) if line.starts_with(prefix) => line[2..].to_string(),
+            _ => return Err(ParseError::new(n, 1, &format!("expected \"{}\"", prefix))),
+        };
+        hunk.lines.push(if prefix == "< " {
+            PatchLine::Removed(text)
+        } else {
+            PatchLine::Added(text)
+        });
+        n += 1;
+        if lines.get(n).is_some_and(|line| line.starts_with('\\')) {
+            missing_newline(hunk, n)?;
+            n += 1;
+        }
+    }
+    Ok(n)
+}
+
+/// Reads through a header line, keeping track of the column for errors
+struct Cursor<'a> {
+    text: &'a str,
+    /// The byte offset of the next character
+    position: usize,
+    /// The 0-based line number
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str, line: usize) -> Cursor<'a> {
+        Cursor {
+            text,
+            position: 0,
+            line,
+        }
+    }
+
+    /// The 1-based column of the next character
+    fn column(&self) -> usize {
+        self.text[..self.position].chars().count() + 1
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::new(self.line, self.column(), message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        if let Some(c) = c {
+            self.position += c.len_utf8();
+        }
+        c
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), ParseError> {
+        if self.text[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected \"{}\"", literal)))
+        }
+    }
+
+    fn end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("unexpected text after the command")),
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, ParseError> {
+        let digits = self.text[self.position..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return Err(self.error("expected a number"));
+        }
+        let number = self.text[self.position..self.position + digits]
+            .parse()
+            .map_err(|_| self.error("number too large"))?;
+        self.position += digits;
+        Ok(number)
+    }
+
+    /// Reads "start[,count]" from a "@@" line as a 0-based range
+    fn unified_range(&mut self) -> Result<Range<usize>, ParseError> {
+        let start = self.number()?;
+        let count = if self.peek() == Some(',') {
+            self.next();
+            self.number()?
+        } else {
+            1
+        };
+        match (start, count) {
+            // an empty range is numbered by the line before it
+            (start, 0) => Ok(start..start),
+            (0, _) => Err(self.error("line numbers start at 1")),
+            (start, count) => {
+                match (start - 1).checked_add(count) {
+                    Some(end) => Ok(start - 1..end),
+                    None => Err(self.error("range too large")),
+                }
+            }
+        }
+    }
+
+    /// Reads "first[,last]" from a normal format command, as given
+    fn normal_range(&mut self) -> Result<(usize, usize), ParseError> {
+        let first = self.number()?;
+        if self.peek() != Some(',') {
+            return Ok((first, first));
+        }
+        self.next();
+        let last = self.number()?;
+        if last < first {
+            return Err(self.error("range ends before it starts"));
+        }
+        Ok((first, last))
+    }
+}
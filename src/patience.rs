@@ -0,0 +1,133 @@
+use diffhelpers::EditFlags;
+use myers::linear_myers_diffs;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+/// A piece of work for patience_diffs, handled in stack order
+enum Task {
+    /// Diff from[from_range] against to[to_range]
+    Gap(Range<usize>, Range<usize>),
+    /// An anchor line, unchanged in both sequences
+    Anchor,
+}
+
+/// Find all the edits necessary to make "from" match "to" using patience diff.
+///
+/// Items that occur exactly once in both sequences are used as anchors:
+/// the longest run of them appearing in the same order is kept unchanged,
+/// and the gaps between consecutive anchors are diffed the same way. Gaps
+/// without any unique items fall back to Myers' algorithm.
+/// Produces the same kind of Vec<EditFlags> as make_diffs.
+pub fn patience_diffs<T: Eq + Hash>(from: &[T], to: &[T]) -> Vec<EditFlags> {
+    let mut diffs = Vec::with_capacity(from.len() + to.len());
+    let mut tasks = vec![Task::Gap(0..from.len(), 0..to.len())];
+
+    while let Some(task) = tasks.pop() {
+        let (mut from_range, mut to_range) = match task {
+            Task::Anchor => {
+                diffs.push(EditFlags::Same);
+                continue;
+            }
+            Task::Gap(from_range, to_range) => (from_range, to_range),
+        };
+
+        // unchanged items at either end are never worth anchoring on
+        while from_range.start < from_range.end && to_range.start < to_range.end &&
+              from[from_range.start] == to[to_range.start] {
+            diffs.push(EditFlags::Same);
+            from_range.start += 1;
+            to_range.start += 1;
+        }
+        let mut suffix = 0;
+        while from_range.start < from_range.end && to_range.start < to_range.end &&
+              from[from_range.end - 1] == to[to_range.end - 1] {
+            suffix += 1;
+            from_range.end -= 1;
+            to_range.end -= 1;
+        }
+        for _ in 0..suffix {
+            tasks.push(Task::Anchor);
+        }
+
+        let anchors = unique_anchors(from, from_range.clone(), to, to_range.clone());
+        if anchors.is_empty() {
+            diffs.extend(linear_myers_diffs(&from[from_range], &to[to_range]));
+            continue;
+        }
+
+        // push the gaps and anchors in reverse so they are handled in order
+        let (mut from_end, mut to_end) = (from_range.end, to_range.end);
+        for &(i, j) in anchors.iter().rev() {
+            tasks.push(Task::Gap(i + 1..from_end, j + 1..to_end));
+            tasks.push(Task::Anchor);
+            from_end = i;
+            to_end = j;
+        }
+        tasks.push(Task::Gap(from_range.start..from_end, to_range.start..to_end));
+    }
+
+    diffs
+}
+
+/// Helper function for patience_diffs
+/// Finds the items that occur exactly once in both ranges and returns the
+/// longest sequence of them whose positions increase in both, as
+/// (from index, to index) pairs.
+fn unique_anchors<T: Eq + Hash>(from: &[T],
+                                from_range: Range<usize>,
+                                to: &[T],
+                                to_range: Range<usize>)
+                                -> Vec<(usize, usize)> {
+    // item -> (occurrences in from, index in from, occurrences in to, index in to)
+    let mut counts: HashMap<&T, (usize, usize, usize, usize)> = HashMap::new();
+    for i in from_range.clone() {
+        let entry = counts.entry(&from[i]).or_insert((0, i, 0, 0));
+        entry.0 += 1;
+    }
+    for j in to_range {
+        if let Some(entry) = counts.get_mut(&to[j]) {
+            entry.2 += 1;
+            entry.3 = j;
+        }
+    }
+
+    let candidates: Vec<(usize, usize)> = from_range.filter_map(|i| match counts[&from[i]] {
+            (1, _, 1, j) => Some((i, j)),
+            _ => None,
+        })
+        .collect();
+
+    longest_increasing(&candidates)
+}
+
+/// Helper function for unique_anchors
+/// Patience sorting: returns the longest subsequence of pairs (already
+/// ordered by their first element) whose second elements are increasing.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // the index into pairs of the top card of each pile
+    let mut tops: Vec<usize> = vec![];
+    // for each card, the top of the previous pile when it was placed
+    let mut prev: Vec<Option<usize>> = Vec::with_capacity(pairs.len());
+
+    for (n, &(_, j)) in pairs.iter().enumerate() {
+        let pile = match tops.binary_search_by(|&top| pairs[top].1.cmp(&j)) {
+            Ok(pile) | Err(pile) => pile,
+        };
+        prev.push(if pile > 0 { Some(tops[pile - 1]) } else { None });
+        if pile == tops.len() {
+            tops.push(n);
+        } else {
+            tops[pile] = n;
+        }
+    }
+
+    let mut result = vec![];
+    let mut card = tops.last().cloned();
+    while let Some(n) = card {
+        result.push(pairs[n]);
+        card = prev[n];
+    }
+    result.reverse();
+    result
+}
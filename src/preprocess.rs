@@ -0,0 +1,43 @@
+use diffhelpers::EditFlags;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Number of equal items at the start of both slices
+pub fn common_prefix_len<T: PartialEq>(from: &[T], to: &[T]) -> usize {
+    from.iter().zip(to).take_while(|&(a, b)| a == b).count()
+}
+
+/// Number of equal items at the end of both slices
+pub fn common_suffix_len<T: PartialEq>(from: &[T], to: &[T]) -> usize {
+    from.iter().rev().zip(to.iter().rev()).take_while(|&(a, b)| a == b).count()
+}
+
+/// Strips the common prefix and suffix of "from" and "to" before calculating
+/// the edits for what is left with "diffs", then adds them back as Same edits.
+/// The result lines up with the original slices.
+pub fn trimmed_diffs<T, F>(from: &[T], to: &[T], diffs: F) -> Vec<EditFlags>
+    where T: PartialEq,
+          F: FnOnce(&[T], &[T]) -> Vec<EditFlags>
+{
+    let prefix = common_prefix_len(from, to);
+    let suffix = common_suffix_len(&from[prefix..], &to[prefix..]);
+
+    let mut result = vec![EditFlags::Same; prefix];
+    result.extend(diffs(&from[prefix..from.len() - suffix], &to[prefix..to.len() - suffix]));
+    result.extend(vec![EditFlags::Same; suffix]);
+    result
+}
+
+/// Replaces every item of "from" and "to" with an integer ID, so that equal
+/// items get the same ID. Comparing the IDs is then as good as comparing the
+/// items, without hashing or comparing the items again.
+pub fn intern<T: Eq + Hash>(from: &[T], to: &[T]) -> (Vec<usize>, Vec<usize>) {
+    let mut ids: HashMap<&T, usize> = HashMap::new();
+    let mut id = |item| {
+        let next = ids.len();
+        *ids.entry(item).or_insert(next)
+    };
+    let from_ids = from.iter().map(&mut id).collect();
+    let to_ids = to.iter().map(&mut id).collect();
+    (from_ids, to_ids)
+}
@@ -0,0 +1,80 @@
+use colored::*;
+use diffhelpers::EditFlags;
+use std::io::{self, Write};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use word_diff::diff_tokens;
+
+/// The least similarity (see refine_line) two lines need for their
+/// differences to be highlighted within them. Below this, so little is left
+/// in common that marking it would be noise, and the lines are shown whole.
+pub const MIN_SIMILARITY: f64 = 0.5;
+
+/// Diffs two strings character by character, where a character is a
+/// grapheme cluster, so an accented letter or an emoji sequence is never
+/// split. The result is in the same form as diff_words.
+///
+/// # Example
+/// ```
+/// use diff::{diff_chars, EditFlags};
+///
+/// assert_eq!(diff_chars("colour", "color"),
+///            vec![(EditFlags::Same, "colo"),
+///                 (EditFlags::Delete, "u"),
+///                 (EditFlags::Same, "r")]);
+/// ```
+pub fn diff_chars<'a>(from: &'a str, to: &'a str) -> Vec<(EditFlags, &'a str)> {
+    diff_tokens(from, &graphemes(from), to, &graphemes(to))
+}
+
+/// Diffs two versions of a line character by character, as diff_chars, if
+/// they are similar enough to be worth it: at least MIN_SIMILARITY of their
+/// characters must be in common, counting both lines. Returns None for
+/// lines that are too different.
+///
+/// # Example
+/// ```
+/// use diff::{refine_line, EditFlags};
+///
+/// let pieces = refine_line("let x = 1;", "let y = 1;").unwrap();
+/// assert_eq!(pieces[1], (EditFlags::Delete, "x"));
+///
+/// assert_eq!(refine_line("let x = 1;", "return;"), None);
+/// ```
+pub fn refine_line<'a>(from: &'a str, to: &'a str) -> Option<Vec<(EditFlags, &'a str)>> {
+    let (from_chars, to_chars) = (graphemes(from), graphemes(to));
+    let pieces = diff_tokens(from, &from_chars, to, &to_chars);
+    let total = from_chars.len() + to_chars.len();
+    let common: usize = pieces.iter()
+        .filter(|&&(flag, _)| flag == EditFlags::Same)
+        .map(|&(_, piece)| piece.graphemes(true).count())
+        .sum();
+    if total == 0 || (2 * common) as f64 / total as f64 >= MIN_SIMILARITY {
+        Some(pieces)
+    } else {
+        None
+    }
+}
+
+/// Writes one side of a line refined by refine_line: its unchanged pieces
+/// in color and the pieces only it has in reversed color. flag says which
+/// side, Delete for the old line or Add for the new one.
+pub fn write_refined<W: Write>(writer: &mut W,
+                               pieces: &[(EditFlags, &str)],
+                               flag: EditFlags,
+                               color: Color)
+                               -> io::Result<()> {
+    for &(piece_flag, piece) in pieces {
+        if piece_flag == EditFlags::Same {
+            write!(writer, "{}", piece.color(color))?;
+        } else if piece_flag == flag {
+            write!(writer, "{}", piece.color(color).reversed())?;
+        }
+    }
+    Ok(())
+}
+
+/// The byte ranges of the grapheme clusters of text
+fn graphemes(text: &str) -> Vec<Range<usize>> {
+    text.grapheme_indices(true).map(|(i, grapheme)| i..i + grapheme.len()).collect()
+}
@@ -0,0 +1,292 @@
+use algorithm::{DiffAlgorithm, LinearMyers};
+use diffhelpers::EditFlags;
+use diffitem::DiffItem;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// Options for write_side_by_side
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SideBySideOptions {
+    /// The total width of each output line (diff -W), 130 by default
+    pub width: usize,
+    /// Print unchanged lines only in the left column, marked with "("
+    pub left_column: bool,
+    /// Leave out unchanged lines entirely
+    pub suppress_common_lines: bool,
+    /// Tabs in the input are expanded to multiples of this many columns
+    pub tab_size: usize,
+}
+
+impl Default for SideBySideOptions {
+    fn default() -> SideBySideOptions {
+        SideBySideOptions {
+            width: 130,
+            left_column: false,
+            suppress_common_lines: false,
+            tab_size: 8,
+        }
+    }
+}
+
+/// Writes an edit script as two columns (diff -y), "from" on the left and
+/// "to" on the right, with tabs expanded to spaces.
+///
+/// The gutter between the columns marks lines that differ with "|", lines
+/// only in "from" with "<" and lines only in "to" with ">". Lines too long
+/// for their column are cut off. If the edit script leaves out changes, as
+/// diff_lines does for ignore_blank_lines, the lines between the edits are
+/// lined up by their text. To show the changes left out where they are,
+/// use write_side_by_side_marked.
+///
+/// # Example
+/// ```
+/// use diff::{diff, write_side_by_side, SideBySideOptions};
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+///
+/// let options = SideBySideOptions { width: 30, ..Default::default() };
+/// let mut output = Vec::new();
+/// write_side_by_side(&mut output, &from, &to, &diff(&from, &to), &options).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "this             this\n\
+///             is               is\n\
+///             an            |  another\n\
+///             example          example\n");
+/// ```
+pub fn write_side_by_side<'a, T, W>(mut writer: W,
+                                    from: &'a [T],
+                                    to: &'a [T],
+                                    diffs: &[DiffItem<'a, T>],
+                                    options: &SideBySideOptions)
+                                    -> io::Result<()>
+    where T: Display,
+          W: Write
+{
+    write_rows(&mut writer, from, to, diffs.iter().map(|item| (item, true)), options)
+}
+
+/// Writes an edit script from diff_lines_marked as two columns, like
+/// write_side_by_side. The changes diff_lines would leave out are shown
+/// where they are, with nothing in the gutter, so the rows after them still
+/// pair up the right lines.
+///
+/// # Example
+/// ```
+/// use diff::{diff_lines_marked, write_side_by_side_marked, DiffOptions, SideBySideOptions};
+///
+/// let from = vec!["a", "", "b"];
+/// let to = vec!["a", "b", "c"];
+///
+/// let options = DiffOptions { ignore_blank_lines: true, ..Default::default() };
+/// let diffs = diff_lines_marked(&from, &to, &options);
+/// let side_by_side = SideBySideOptions { width: 30, ..Default::default() };
+/// let mut output = Vec::new();
+/// write_side_by_side_marked(&mut output, &from, &to, &diffs, &side_by_side).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "a                a\n\nb                b\n\
+///             \x20             >  c\n");
+/// ```
+pub fn write_side_by_side_marked<'a, T, W>(mut writer: W,
+                                           from: &'a [T],
+                                           to: &'a [T],
+                                           diffs: &[(DiffItem<'a, T>, bool)],
+                                           options: &SideBySideOptions)
+                                           -> io::Result<()>
+    where T: Display,
+          W: Write
+{
+    write_rows(&mut writer,
+               from,
+               to,
+               diffs.iter().map(|&(ref item, shown)| (item, shown)),
+               options)
+}
+
+/// Writes side by side rows for edits paired with whether they are shown
+fn write_rows<'d, 'a: 'd, T, W, I>(writer: &mut W,
+                                   from: &'a [T],
+                                   to: &'a [T],
+                                   diffs: I,
+                                   options: &SideBySideOptions)
+                                   -> io::Result<()>
+    where T: Display + 'a,
+          W: Write,
+          I: IntoIterator<Item = (&'d DiffItem<'a, T>, bool)>
+{
+    let columns = Columns::new(options);
+    let (mut i, mut j) = (0, 0);
+    for (item, shown) in diffs {
+        let (removed, inserted) = (item.from_range(), item.to_range());
+        write_unchanged(writer,
+                        &columns,
+                        lines_between(from, i, removed.start),
+                        lines_between(to, j, inserted.start),
+                        options)?;
+
+        let (deleted, added) = (item.removed(), item.inserted());
+        if !shown {
+            if !options.suppress_common_lines {
+                for n in 0..deleted.len().max(added.len()) {
+                    columns.write_row(writer, deleted.get(n), ' ', added.get(n))?;
+                }
+            }
+        } else {
+            for n in 0..deleted.len().max(added.len()) {
+                match (deleted.get(n), added.get(n)) {
+                    (Some(left), Some(right)) => {
+                        columns.write_row(writer, Some(left), '|', Some(right))?
+                    }
+                    (Some(left), None) => columns.write_row(writer, Some(left), '<', None)?,
+                    (None, right) => columns.write_row(writer, None, '>', right)?,
+                }
+            }
+        }
+        i = removed.end;
+        j = inserted.end;
+    }
+    write_unchanged(writer,
+                    &columns,
+                    lines_between(from, i, from.len()),
+                    lines_between(to, j, to.len()),
+                    options)
+}
+
+/// Writes the lines between two edits. These are normally the same number
+/// of lines on both sides, each pair shown as one row. If the edit script
+/// left some changes out, the two sides are lined up by diffing their
+/// text, and the lines that are only on one side are shown on their own
+/// with nothing in the gutter.
+fn write_unchanged<T, W>(writer: &mut W,
+                         columns: &Columns,
+                         from: &[T],
+                         to: &[T],
+                         options: &SideBySideOptions)
+                         -> io::Result<()>
+    where T: Display,
+          W: Write
+{
+    if options.suppress_common_lines {
+        return Ok(());
+    }
+    let common = |writer: &mut W, left: &T, right: &T| {
+        if options.left_column {
+            columns.write_row(writer, Some(left), '(', None)
+        } else {
+            columns.write_row(writer, Some(left), ' ', Some(right))
+        }
+    };
+
+    if from.len() == to.len() {
+        for (left, right) in from.iter().zip(to) {
+            common(writer, left, right)?;
+        }
+        return Ok(());
+    }
+
+    let from_text: Vec<String> = from.iter().map(|line| line.to_string()).collect();
+    let to_text: Vec<String> = to.iter().map(|line| line.to_string()).collect();
+    let (mut i, mut j) = (0, 0);
+    for edit in LinearMyers.diffs(&from_text, &to_text) {
+        match edit {
+            EditFlags::Same => {
+                common(writer, &from[i], &to[j])?;
+                i += 1;
+                j += 1;
+            }
+            EditFlags::Delete => {
+                columns.write_row(writer, Some(&from[i]), ' ', None)?;
+                i += 1;
+            }
+            EditFlags::Add => {
+                columns.write_row(writer, None, ' ', Some(&to[j]))?;
+                j += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The lines from start up to end, or as many of them as there are
+fn lines_between<T>(lines: &[T], start: usize, end: usize) -> &[T] {
+    let end = end.min(lines.len());
+    &lines[start.min(end)..end]
+}
+
+/// Where everything goes on a side by side line, laid out as GNU diff -y -t does
+struct Columns {
+    /// The width of each column of text
+    half_width: usize,
+    /// Where the right column starts
+    right_offset: usize,
+    tab_size: usize,
+}
+
+impl Columns {
+    fn new(options: &SideBySideOptions) -> Columns {
+        // a gutter of at least 3 columns, the rest split evenly
+        let offset = (options.width + 4) / 2;
+        let half_width = offset.saturating_sub(3).min(options.width.saturating_sub(offset));
+        Columns {
+            half_width,
+            right_offset: if half_width > 0 { offset } else { options.width },
+            tab_size: options.tab_size.max(1),
+        }
+    }
+
+    /// Writes one line of output; the separator goes in the gutter unless
+    /// it is a space
+    fn write_row<W: Write, T: Display>(&self,
+                                       writer: &mut W,
+                                       left: Option<&T>,
+                                       separator: char,
+                                       right: Option<&T>)
+                                       -> io::Result<()> {
+        let mut line = String::new();
+        if let Some(left) = left {
+            self.push_column(&mut line, &left.to_string());
+        }
+        if separator != ' ' {
+            pad(&mut line, (self.half_width + self.right_offset - 1) / 2);
+            line.push(separator);
+        }
+        if let Some(right) = right {
+            let right = right.to_string();
+            if !right.is_empty() {
+                pad(&mut line, self.right_offset);
+                self.push_column(&mut line, &right);
+            }
+        }
+        writeln!(writer, "{}", line)
+    }
+
+    /// Appends text to a line, expanding tabs and cutting it off at the
+    /// width of a column
+    fn push_column(&self, line: &mut String, text: &str) {
+        let mut column = 0;
+        for c in text.chars() {
+            if c == '\t' {
+                let next = (column / self.tab_size + 1) * self.tab_size;
+                let next = next.min(self.half_width);
+                for _ in column..next {
+                    line.push(' ');
+                }
+                column = next;
+            } else if column < self.half_width {
+                line.push(c);
+                column += 1;
+            }
+            if column >= self.half_width {
+                break;
+            }
+        }
+    }
+}
+
+/// Pads a line with spaces up to the given column
+fn pad(line: &mut String, column: usize) {
+    let len = line.chars().count();
+    for _ in len..column {
+        line.push(' ');
+    }
+}
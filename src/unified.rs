@@ -0,0 +1,141 @@
+use header::FileHeader;
+use hunks::{hunks, EditScript};
+use options::MissingNewline;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Writes an edit script in unified format (diff -u), with "context"
+/// unchanged lines around every change. Nothing is written if there are no
+/// changes. Call write_unified_header first to produce a complete diff.
+///
+/// # Example
+/// ```
+/// use diff::{diff, write_unified};
+///
+/// let from = vec!["this", "is", "an", "example"];
+/// let to = vec!["this", "is", "another", "example"];
+///
+/// let mut output = Vec::new();
+/// write_unified(&mut output, &from, &to, &diff(&from, &to), 1).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "@@ -2,3 +2,3 @@\n is\n-an\n+another\n example\n");
+/// ```
+pub fn write_unified<'a, T, W, S>(writer: W,
+                                  from: &'a [T],
+                                  to: &'a [T],
+                                  diffs: &S,
+                                  context: usize)
+                                  -> io::Result<()>
+    where T: Display,
+          W: Write,
+          S: EditScript<'a, T> + ?Sized
+{
+    write_unified_file(writer, from, to, diffs, context, MissingNewline::default())
+}
+
+/// Writes an edit script in unified format, like write_unified, for files
+/// that may not end with a newline. A last line without one is followed by
+/// "\ No newline at end of file" wherever it is shown. The script must
+/// treat such a line as different from one with a newline, as diff_lines
+/// does when told which files are missing it.
+///
+/// # Example
+/// ```
+/// use diff::{diff_lines, write_unified_file, DiffOptions, MissingNewline};
+///
+/// let from = vec!["a", "b"];
+/// let to = vec!["a", "b"];
+///
+/// let missing_newline = MissingNewline { from: false, to: true };
+/// let options = DiffOptions { missing_newline, ..Default::default() };
+/// let diffs = diff_lines(&from, &to, &options);
+/// let mut output = Vec::new();
+/// write_unified_file(&mut output, &from, &to, &diffs, 1, missing_newline).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n");
+/// ```
+pub fn write_unified_file<'a, T, W, S>(mut writer: W,
+                                       from: &'a [T],
+                                       to: &'a [T],
+                                       diffs: &S,
+                                       context: usize,
+                                       missing_newline: MissingNewline)
+                                       -> io::Result<()>
+    where T: Display,
+          W: Write,
+          S: EditScript<'a, T> + ?Sized
+{
+    let from_last = |n: usize| missing_newline.from && n + 1 == from.len();
+    let to_last = |n: usize| missing_newline.to && n + 1 == to.len();
+
+    for hunk in hunks(diffs, from.len(), to.len(), context) {
+        writeln!(writer,
+                 "@@ -{} +{} @@",
+                 unified_range(&hunk.from),
+                 unified_range(&hunk.to))?;
+
+        let mut line = hunk.from.start;
+        for item in hunk.items {
+            let (removed, inserted) = (item.from_range(), item.to_range());
+            for (n, common) in from.iter().enumerate().take(removed.start).skip(line) {
+                write_line(&mut writer, " ", common, from_last(n))?;
+            }
+            for (n, deleted) in removed.clone().zip(item.removed()) {
+                write_line(&mut writer, "-", deleted, from_last(n))?;
+            }
+            for (n, added) in inserted.zip(item.inserted()) {
+                write_line(&mut writer, "+", added, to_last(n))?;
+            }
+            line = removed.end;
+        }
+        // context at the end of "from" is at the end of "to" as well
+        for (n, common) in from.iter().enumerate().take(hunk.from.end).skip(line) {
+            write_line(&mut writer, " ", common, from_last(n))?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes one line of a hunk, followed by the marker for a missing newline
+/// if it is the last line of a file that has none
+pub fn write_line<W: Write, T: Display>(writer: &mut W,
+                                        prefix: &str,
+                                        line: &T,
+                                        missing_newline: bool)
+                                        -> io::Result<()> {
+    writeln!(writer, "{}{}", prefix, line)?;
+    if missing_newline {
+        writeln!(writer, "\\ No newline at end of file")?;
+    }
+    Ok(())
+}
+
+/// Writes the "---" and "+++" lines that start a unified diff
+///
+/// # Example
+/// ```
+/// use diff::{write_unified_header, FileHeader};
+///
+/// let mut output = Vec::new();
+/// write_unified_header(&mut output, &FileHeader::new("a.txt"), &FileHeader::new("b.txt"))
+///     .unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "--- a.txt\n+++ b.txt\n");
+/// ```
+pub fn write_unified_header<W: Write>(mut writer: W,
+                                      from: &FileHeader,
+                                      to: &FileHeader)
+                                      -> io::Result<()> {
+    writeln!(writer, "--- {}", from)?;
+    writeln!(writer, "+++ {}", to)
+}
+
+/// Formats a 0-based range of lines as "start,count", leaving out the count
+/// when it is 1. An empty range is shown starting at the line before it.
+pub fn unified_range(range: &Range<usize>) -> String {
+    match range.end - range.start {
+        0 => format!("{},0", range.start),
+        1 => format!("{}", range.start + 1),
+        count => format!("{},{}", range.start + 1, count),
+    }
+}
@@ -0,0 +1,195 @@
+use algorithm::{DiffAlgorithm, LinearMyers};
+use colored::*;
+use diffhelpers::EditFlags;
+use hunks::{hunks, EditScript};
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
+use unified::unified_range;
+
+/// How write_word_diff marks words that changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordStyle {
+    /// Removed words as "[-old-]" and added words as "{+new+}"
+    Markup,
+    /// Removed words in red and added words in green
+    Color,
+}
+
+/// Diffs two strings word by word. Words are runs of letters, digits and
+/// underscores; every other character, including whitespace and newlines,
+/// is compared on its own.
+///
+/// The result covers all of both strings in order: Same pieces are in
+/// both, Delete pieces only in "from" and Add pieces only in "to". Between
+/// two Same pieces, removed text always comes before added text.
+///
+/// # Example
+/// ```
+/// use diff::{diff_words, EditFlags};
+///
+/// assert_eq!(diff_words("the quick fox", "the slow fox"),
+///            vec![(EditFlags::Same, "the "),
+///                 (EditFlags::Delete, "quick"),
+///                 (EditFlags::Add, "slow"),
+///                 (EditFlags::Same, " fox")]);
+/// ```
+pub fn diff_words<'a>(from: &'a str, to: &'a str) -> Vec<(EditFlags, &'a str)> {
+    diff_tokens(from, &tokenize(from), to, &tokenize(to))
+}
+
+/// Diffs two strings split into tokens, given as byte ranges that cover
+/// each string in order, and joins the tokens into pieces as diff_words
+/// describes
+pub fn diff_tokens<'a>(from: &'a str,
+                       from_tokens: &[Range<usize>],
+                       to: &'a str,
+                       to_tokens: &[Range<usize>])
+                       -> Vec<(EditFlags, &'a str)> {
+    let from_words: Vec<&str> = from_tokens.iter().map(|range| &from[range.clone()]).collect();
+    let to_words: Vec<&str> = to_tokens.iter().map(|range| &to[range.clone()]).collect();
+
+    // byte ranges of "to" for Add pieces, and of "from" for the others
+    let mut pieces = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let (mut from_end, mut to_end) = (0, 0);
+    for edit in LinearMyers.diffs(&from_words, &to_words) {
+        match edit {
+            EditFlags::Same => {
+                push_piece(&mut pieces, EditFlags::Delete, from_end..from_tokens[i].start);
+                push_piece(&mut pieces, EditFlags::Add, to_end..to_tokens[j].start);
+                push_piece(&mut pieces, EditFlags::Same, from_tokens[i].clone());
+                from_end = from_tokens[i].end;
+                to_end = to_tokens[j].end;
+                i += 1;
+                j += 1;
+            }
+            EditFlags::Delete => i += 1,
+            EditFlags::Add => j += 1,
+        }
+    }
+    push_piece(&mut pieces, EditFlags::Delete, from_end..from.len());
+    push_piece(&mut pieces, EditFlags::Add, to_end..to.len());
+
+    pieces.into_iter()
+        .map(|(flag, range)| match flag {
+            EditFlags::Add => (flag, &to[range]),
+            _ => (flag, &from[range]),
+        })
+        .collect()
+}
+
+/// Adds a non-empty piece to the result of diff_words, joining it to the
+/// piece before if that is the same kind
+fn push_piece(pieces: &mut Vec<(EditFlags, Range<usize>)>, flag: EditFlags, range: Range<usize>) {
+    if range.start == range.end {
+        return;
+    }
+    match pieces.last_mut() {
+        Some(&mut (last, ref mut previous)) if last == flag && previous.end == range.start => {
+            previous.end = range.end
+        }
+        _ => pieces.push((flag, range)),
+    }
+}
+
+/// Writes an edit script as hunks with "context" unchanged lines around
+/// every change, like a unified diff, but with each change shown inline:
+/// the lines it removes and adds are diffed word by word with diff_words.
+/// Nothing is written if there are no changes.
+///
+/// # Example
+/// ```
+/// use diff::{diff, write_word_diff, WordStyle};
+///
+/// let from = vec!["this is", "an example"];
+/// let to = vec!["this is", "another example"];
+///
+/// let mut output = Vec::new();
+/// write_word_diff(&mut output, &from, &to, &diff(&from, &to), 1, WordStyle::Markup).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(),
+///            "@@ -1,2 +1,2 @@\nthis is\n[-an-]{+another+} example\n");
+/// ```
+pub fn write_word_diff<'a, T, W, S>(mut writer: W,
+                                    from: &'a [T],
+                                    to: &'a [T],
+                                    diffs: &S,
+                                    context: usize,
+                                    style: WordStyle)
+                                    -> io::Result<()>
+    where T: Display,
+          W: Write,
+          S: EditScript<'a, T> + ?Sized
+{
+    for hunk in hunks(diffs, from.len(), to.len(), context) {
+        writeln!(writer,
+                 "@@ -{} +{} @@",
+                 unified_range(&hunk.from),
+                 unified_range(&hunk.to))?;
+
+        let mut line = hunk.from.start;
+        for item in hunk.items {
+            let removed = item.from_range();
+            for common in &from[line..removed.start] {
+                writeln!(writer, "{}", common)?;
+            }
+            let removed_text = join_lines(item.removed());
+            let inserted_text = join_lines(item.inserted());
+            for (flag, piece) in diff_words(&removed_text, &inserted_text) {
+                // markup doesn't span lines, so each line gets its own
+                let mut lines = piece.split('\n');
+                if let Some(first) = lines.next() {
+                    write_piece(&mut writer, flag, first, style)?;
+                }
+                for next in lines {
+                    writeln!(writer)?;
+                    write_piece(&mut writer, flag, next, style)?;
+                }
+            }
+            writeln!(writer)?;
+            line = removed.end;
+        }
+        for common in &from[line..hunk.from.end] {
+            writeln!(writer, "{}", common)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes part of a line from diff_words, marked as style says
+fn write_piece<W: Write>(writer: &mut W,
+                         flag: EditFlags,
+                         text: &str,
+                         style: WordStyle)
+                         -> io::Result<()> {
+    if text.is_empty() {
+        return Ok(());
+    }
+    match (flag, style) {
+        (EditFlags::Same, _) => write!(writer, "{}", text),
+        (EditFlags::Delete, WordStyle::Markup) => write!(writer, "[-{}-]", text),
+        (EditFlags::Add, WordStyle::Markup) => write!(writer, "{{+{}+}}", text),
+        (EditFlags::Delete, WordStyle::Color) => write!(writer, "{}", text.red()),
+        (EditFlags::Add, WordStyle::Color) => write!(writer, "{}", text.green()),
+    }
+}
+
+fn join_lines<T: Display>(lines: &[T]) -> String {
+    lines.iter().map(|line| line.to_string()).collect::<Vec<_>>().join("\n")
+}
+
+/// Splits text into words (runs of alphanumeric characters and
+/// underscores) and single other characters, as byte ranges
+fn tokenize(text: &str) -> Vec<Range<usize>> {
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let mut in_word = false;
+    for (i, c) in text.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_';
+        match tokens.last_mut() {
+            Some(word) if is_word && in_word => word.end = i + c.len_utf8(),
+            _ => tokens.push(i..i + c.len_utf8()),
+        }
+        in_word = is_word;
+    }
+    tokens
+}